@@ -1,17 +1,82 @@
+use std::convert::TryFrom;
+use std::io;
 use std::net::UdpSocket;
+use std::process;
 use std::thread;
 
+use lole::export::{jsonl::JsonLinesExporter, Filter};
+use lole::frame::PacketType;
+
+const USAGE: &str = "Print frames as JSON Lines.
+
+Usage: main [OPTIONS]
+
+Options:
+    --packet-types <IDS>  Only print frames of these packet ids, like 0,2
+    --cars <INDICES>      Only print data of the cars with these vehicle indices, like 0,1
+    --fields <FIELDS>     Only print these fields of the frame bodies, like lap_distance
+    --wall-clock          Add the wall-clock time the frame was received";
+
 fn main() {
+    let mut filter = Filter::new();
+    let mut fields = None;
+    let mut wall_clock = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--packet-types" => {
+                let packet_types = numbers(args.next())
+                    .into_iter()
+                    .map(|id| PacketType::try_from(id).unwrap_or_else(|e| exit(&e.to_string())))
+                    .collect();
+                filter = filter.packet_types(packet_types);
+            }
+            "--cars" => filter = filter.cars(numbers(args.next())),
+            "--fields" => {
+                let value = args.next().unwrap_or_else(|| exit("Missing fields."));
+                fields = Some(value.split(',').map(String::from).collect());
+            }
+            "--wall-clock" => wall_clock = true,
+            _ => exit(&format!("Unknown option '{}'.", arg)),
+        }
+    }
+
     let socket = UdpSocket::bind("0.0.0.0:20777").expect("Failed to bind to '0.0.0.0:20777'");
     let mut app = lole::telemetry::App::new(socket);
 
-    let frames = app.frames();
+    let frames = app.received_frames();
 
-    thread::spawn(move || {
-        for frame in frames {
-            dbg!(frame);
-        }
-    });
+    thread::spawn(move || app.start().expect("Lole crashed."));
+
+    let stdout = io::stdout();
+    let mut exporter = JsonLinesExporter::new(stdout.lock())
+        .with_filter(filter)
+        .with_wall_clock(wall_clock);
+    if let Some(fields) = fields {
+        exporter = exporter.with_fields(fields);
+    }
+
+    exporter
+        .export_received(frames)
+        .expect("Failed to export frames.");
+}
+
+// Parse a comma separated list of numbers.
+fn numbers(value: Option<String>) -> Vec<u8> {
+    let value = value.unwrap_or_else(|| exit("Missing list of numbers."));
+    value
+        .split(',')
+        .map(|number| {
+            number
+                .trim()
+                .parse()
+                .unwrap_or_else(|_| exit(&format!("'{}' is not a valid number.", number)))
+        })
+        .collect()
+}
 
-    app.start().expect("Lole crashed.");
+fn exit(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    process::exit(1);
 }
@@ -1,11 +1,26 @@
 use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 use std::{fs::File, net::UdpSocket};
 
-use lole::race::{Race, Status};
+use lole::race::control::ControlLossKind;
+use lole::race::{Race, RaceLines, Status};
 use tera::{Context, Tera};
 
+// Stop plotting if the game didn't send a frame for this long.
+const STALE_TIMEOUT: Duration = Duration::from_secs(10);
+// The plot is written here, unless another path is given as the first argument.
+const DEFAULT_OUTPUT: &str = "/tmp/track.svg";
+
 fn main() {
+    let output = PathBuf::from(
+        std::env::args()
+            .nth(1)
+            .unwrap_or_else(|| DEFAULT_OUTPUT.to_string()),
+    );
     let tera = match Tera::new("templates/*.svg") {
         Ok(t) => t,
         Err(e) => {
@@ -18,41 +33,132 @@ fn main() {
     println!("Started listening on 0.0.0.0:20777.");
 
     let frames = app.frames();
+    let health = app.health();
     thread::spawn(move || app.start().expect("Lole crashed."));
 
-    let mut race = Race::new();
-    for frame in frames {
-        race.feed_frame(frame);
-        if race.status == Status::Finished {
+    let finished = Arc::new(AtomicBool::new(false));
+    // The race line of the whole session is plotted.
+    let mut race = Race::new().with_race_lines(RaceLines::new());
+    race.on_status_change({
+        let finished = finished.clone();
+        move |_, to| {
+            finished.store(
+                matches!(
+                    to,
+                    Status::ChequeredFlag
+                        | Status::RaceWinnerDeclared
+                        | Status::Finished
+                        | Status::Stale
+                ),
+                Ordering::Relaxed,
+            )
+        }
+    });
+    race.on_lap_completed(|car, lap, lap_time| {
+        println!(
+            "{:?} completed lap {} in {:.3}s.",
+            car.participant.driver_id, lap, lap_time
+        )
+    });
+
+    loop {
+        match frames.recv_timeout(STALE_TIMEOUT) {
+            Some(frame) => {
+                race.feed_frame(frame);
+            }
+            // Keep waiting until the game starts sending frames.
+            None if health.frames() == 0 => continue,
+            None => {
+                println!("No frames received for {:?}.", STALE_TIMEOUT);
+                race.mark_stale();
+            }
+        }
+        if finished.load(Ordering::Relaxed) {
             break;
         }
     }
 
-    let human_driver = race
-        .participants
-        .iter()
-        .find(|participant| participant.ai_controlled == 0)
-        .expect("Failed to find driver controlled by a human; all participants AI controlled.")
-        .driver_id;
-
-    let data = race
-        .race_lines
-        .by_driver(human_driver)
-        .to_vec()
-        .iter()
-        .map(|record| (record.coords.0, record.coords.1))
-        .collect();
-
-    let svg = plot(tera, data).expect("Failed to plot race line");
-    let mut f = File::create("/tmp/track.svg").expect("Failed to open /tmp/track.svg");
-    f.write_all(svg.as_bytes())
-        .expect("Failed to write to /tmp/track.svg");
-    println!("Wrote track to file:///tmp/track.svg");
+    let player = race
+        .player()
+        .expect("Failed to find the car of the player; the player is spectating.");
+
+    for loss in race.control_losses() {
+        println!(
+            "{:.3}s: {:?} {} at {:.0}m.",
+            loss.location.timestamp,
+            loss.car.participant.driver_id,
+            kind(&loss.kind),
+            loss.lap_distance.unwrap_or(0.0)
+        );
+    }
+
+    // In split screen mode both players get their own plot.
+    let mut players = vec![(player, output.clone())];
+    if let Some(secondary_player) = race.secondary_player() {
+        players.push((secondary_player, secondary_output(&output)));
+    }
+
+    for (car, path) in players {
+        let id = car.id();
+        let data = race
+            .race_lines
+            .iter_car(id)
+            .map(|record| record.map_point())
+            .collect();
+        let markers = race
+            .control_losses()
+            .iter()
+            .filter(|loss| loss.car.id() == id)
+            .map(|loss| {
+                let (x, z) = loss.location.map_point();
+                (x, z, kind(&loss.kind))
+            })
+            .collect();
+
+        let svg = plot(&tera, data, markers).expect("Failed to plot race line");
+        let mut f =
+            File::create(&path).unwrap_or_else(|_| panic!("Failed to open {}", path.display()));
+        f.write_all(svg.as_bytes())
+            .unwrap_or_else(|_| panic!("Failed to write to {}", path.display()));
+        println!(
+            "Wrote track of {} to file://{}",
+            car.participant.name,
+            path.display()
+        );
+    }
+}
+
+// The plot of the second player is written next to `output`, for example to
+// /tmp/track-2.svg if `output` is /tmp/track.svg.
+fn secondary_output(output: &Path) -> PathBuf {
+    let stem = output
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let name = match output.extension() {
+        Some(extension) => format!("{}-2.{}", stem, extension.to_string_lossy()),
+        None => format!("{}-2", stem),
+    };
+
+    output.with_file_name(name)
+}
+
+fn kind(kind: &ControlLossKind) -> &'static str {
+    match kind {
+        ControlLossKind::Spin => "spin",
+        ControlLossKind::Slide { .. } => "slide",
+        ControlLossKind::WrongWay => "wrong-way",
+    }
 }
 
-fn plot(template: Tera, coords: Vec<(f32, f32)>) -> Result<String, tera::Error> {
+fn plot(
+    template: &Tera,
+    coords: Vec<(f32, f32)>,
+    markers: Vec<(f32, f32, &str)>,
+) -> Result<String, tera::Error> {
     let mut context = Context::new();
     context.insert("coords", &coords[..]);
+    context.insert("markers", &markers[..]);
 
     template.render("track.svg", &context)
 }
@@ -30,6 +30,42 @@ impl std::fmt::Display for ParseError {
             ErrorKind::InvalidDriver(v) => {
                 write!(f, "{:?} is not a valid Driver", v)
             }
+            ErrorKind::InvalidPitStatus(v) => {
+                write!(f, "{:?} is not a valid PitStatus", v)
+            }
+            ErrorKind::InvalidDriverStatus(v) => {
+                write!(f, "{:?} is not a valid DriverStatus", v)
+            }
+            ErrorKind::InvalidResultStatus(v) => {
+                write!(f, "{:?} is not a valid ResultStatus", v)
+            }
+            ErrorKind::InvalidFuelMix(v) => {
+                write!(f, "{:?} is not a valid FuelMix", v)
+            }
+            ErrorKind::InvalidTyreCompound(v) => {
+                write!(f, "{:?} is not a valid TyreCompound", v)
+            }
+            ErrorKind::InvalidVisualTyreCompound(v) => {
+                write!(f, "{:?} is not a valid VisualTyreCompound", v)
+            }
+            ErrorKind::InvalidWeather(v) => {
+                write!(f, "{:?} is not a valid Weather", v)
+            }
+            ErrorKind::InvalidSessionType(v) => {
+                write!(f, "{:?} is not a valid SessionType", v)
+            }
+            ErrorKind::InvalidCircuit(v) => {
+                write!(f, "{:?} is not a valid Circuit", v)
+            }
+            ErrorKind::InvalidFormula(v) => {
+                write!(f, "{:?} is not a valid Formula", v)
+            }
+            ErrorKind::InvalidSafetyCarStatus(v) => {
+                write!(f, "{:?} is not a valid SafetyCarStatus", v)
+            }
+            ErrorKind::InvalidFiaFlag(v) => {
+                write!(f, "{:?} is not a valid FiaFlag", v)
+            }
         }
     }
 }
@@ -41,6 +77,18 @@ pub enum ErrorKind {
     InvalidInfringementType(u8),
     InvalidTeam(u8),
     InvalidDriver(u8),
+    InvalidPitStatus(u8),
+    InvalidDriverStatus(u8),
+    InvalidResultStatus(u8),
+    InvalidFuelMix(u8),
+    InvalidTyreCompound(u8),
+    InvalidVisualTyreCompound(u8),
+    InvalidWeather(u8),
+    InvalidSessionType(u8),
+    InvalidCircuit(i8),
+    InvalidFormula(u8),
+    InvalidSafetyCarStatus(u8),
+    InvalidFiaFlag(i8),
 }
 
 #[derive(Debug)]
@@ -75,3 +123,66 @@ impl std::convert::From<ParseError> for AppError {
 }
 
 impl std::error::Error for AppError {}
+
+/// An error that can occur while exporting frames.
+#[derive(Debug)]
+pub enum ExportError {
+    IOError(std::io::Error),
+    #[cfg(feature = "json")]
+    JsonError(serde_json::Error),
+    #[cfg(feature = "parquet")]
+    ParquetError(parquet::errors::ParquetError),
+    #[cfg(feature = "sqlite")]
+    SqliteError(rusqlite::Error),
+}
+
+impl std::fmt::Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::IOError(e) => {
+                write!(f, "Failed to write export: {:?}", e)
+            }
+            #[cfg(feature = "json")]
+            ExportError::JsonError(e) => {
+                write!(f, "Failed to serialize frame to JSON: {:?}", e)
+            }
+            #[cfg(feature = "parquet")]
+            ExportError::ParquetError(e) => {
+                write!(f, "Failed to write Parquet file: {:?}", e)
+            }
+            #[cfg(feature = "sqlite")]
+            ExportError::SqliteError(e) => {
+                write!(f, "Failed to write to SQLite database: {:?}", e)
+            }
+        }
+    }
+}
+
+impl std::convert::From<std::io::Error> for ExportError {
+    fn from(e: std::io::Error) -> Self {
+        ExportError::IOError(e)
+    }
+}
+
+#[cfg(feature = "json")]
+impl std::convert::From<serde_json::Error> for ExportError {
+    fn from(e: serde_json::Error) -> Self {
+        ExportError::JsonError(e)
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl std::convert::From<parquet::errors::ParquetError> for ExportError {
+    fn from(e: parquet::errors::ParquetError) -> Self {
+        ExportError::ParquetError(e)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl std::convert::From<rusqlite::Error> for ExportError {
+    fn from(e: rusqlite::Error) -> Self {
+        ExportError::SqliteError(e)
+    }
+}
+
+impl std::error::Error for ExportError {}
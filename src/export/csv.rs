@@ -0,0 +1,393 @@
+use std::io::Write;
+
+use crate::error::ExportError;
+use crate::export::Filter;
+use crate::frame::{
+    car_status::CarStatus, lap_data::LapData, motion::CarMotion, Data, Frame, PacketType,
+};
+
+/// `Channel` is a single value of a car that can be exported as a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    /// Position in the game world in metres.
+    PositionX,
+    PositionY,
+    PositionZ,
+    /// Velocity in the game world in metres per second.
+    VelocityX,
+    VelocityY,
+    VelocityZ,
+    /// Speed in metres per second.
+    Speed,
+    GForceLateral,
+    GForceLongitudinal,
+    GForceVertical,
+    /// Rotation in radians.
+    Yaw,
+    Pitch,
+    Roll,
+    /// Distance in metres along the lap.
+    LapDistance,
+    /// Distance in metres driven in the session.
+    TotalDistance,
+    /// Time in seconds of the current lap.
+    CurrentLapTime,
+    /// Time in seconds of the last lap.
+    LastLapTime,
+    CurrentLapNum,
+    CarPosition,
+    Sector,
+    /// Fuel in the tank in kilograms.
+    FuelInTank,
+    FuelRemainingLaps,
+    /// Energy in the ERS store in Joules.
+    ErsStoreEnergy,
+    TyresAgeLaps,
+}
+
+impl Channel {
+    /// All channels, in the order they're defined.
+    pub const ALL: [Channel; 24] = [
+        Channel::PositionX,
+        Channel::PositionY,
+        Channel::PositionZ,
+        Channel::VelocityX,
+        Channel::VelocityY,
+        Channel::VelocityZ,
+        Channel::Speed,
+        Channel::GForceLateral,
+        Channel::GForceLongitudinal,
+        Channel::GForceVertical,
+        Channel::Yaw,
+        Channel::Pitch,
+        Channel::Roll,
+        Channel::LapDistance,
+        Channel::TotalDistance,
+        Channel::CurrentLapTime,
+        Channel::LastLapTime,
+        Channel::CurrentLapNum,
+        Channel::CarPosition,
+        Channel::Sector,
+        Channel::FuelInTank,
+        Channel::FuelRemainingLaps,
+        Channel::ErsStoreEnergy,
+        Channel::TyresAgeLaps,
+    ];
+
+    /// The channels of Motion frames: position, velocity, g-forces and rotation.
+    pub const MOTION: [Channel; 13] = [
+        Channel::PositionX,
+        Channel::PositionY,
+        Channel::PositionZ,
+        Channel::VelocityX,
+        Channel::VelocityY,
+        Channel::VelocityZ,
+        Channel::Speed,
+        Channel::GForceLateral,
+        Channel::GForceLongitudinal,
+        Channel::GForceVertical,
+        Channel::Yaw,
+        Channel::Pitch,
+        Channel::Roll,
+    ];
+
+    /// Name of the column of the channel.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Channel::PositionX => "position_x",
+            Channel::PositionY => "position_y",
+            Channel::PositionZ => "position_z",
+            Channel::VelocityX => "velocity_x",
+            Channel::VelocityY => "velocity_y",
+            Channel::VelocityZ => "velocity_z",
+            Channel::Speed => "speed",
+            Channel::GForceLateral => "g_force_lateral",
+            Channel::GForceLongitudinal => "g_force_longitudinal",
+            Channel::GForceVertical => "g_force_vertical",
+            Channel::Yaw => "yaw",
+            Channel::Pitch => "pitch",
+            Channel::Roll => "roll",
+            Channel::LapDistance => "lap_distance",
+            Channel::TotalDistance => "total_distance",
+            Channel::CurrentLapTime => "current_lap_time",
+            Channel::LastLapTime => "last_lap_time",
+            Channel::CurrentLapNum => "current_lap_num",
+            Channel::CarPosition => "car_position",
+            Channel::Sector => "sector",
+            Channel::FuelInTank => "fuel_in_tank",
+            Channel::FuelRemainingLaps => "fuel_remaining_laps",
+            Channel::ErsStoreEnergy => "ers_store_energy",
+            Channel::TyresAgeLaps => "tyres_age_laps",
+        }
+    }
+
+    /// Find the channel with the column name `name`.
+    pub fn from_name(name: &str) -> Option<Channel> {
+        Channel::ALL
+            .iter()
+            .find(|channel| channel.name() == name)
+            .copied()
+    }
+
+    /// The type of the packets the channel is read from.
+    pub fn packet_type(&self) -> PacketType {
+        match self {
+            Channel::PositionX
+            | Channel::PositionY
+            | Channel::PositionZ
+            | Channel::VelocityX
+            | Channel::VelocityY
+            | Channel::VelocityZ
+            | Channel::Speed
+            | Channel::GForceLateral
+            | Channel::GForceLongitudinal
+            | Channel::GForceVertical
+            | Channel::Yaw
+            | Channel::Pitch
+            | Channel::Roll => PacketType::Motion,
+            Channel::LapDistance
+            | Channel::TotalDistance
+            | Channel::CurrentLapTime
+            | Channel::LastLapTime
+            | Channel::CurrentLapNum
+            | Channel::CarPosition
+            | Channel::Sector => PacketType::LapData,
+            Channel::FuelInTank
+            | Channel::FuelRemainingLaps
+            | Channel::ErsStoreEnergy
+            | Channel::TyresAgeLaps => PacketType::CarStatus,
+        }
+    }
+
+    fn motion_value(&self, motion: &CarMotion) -> Option<f32> {
+        match self {
+            Channel::PositionX => Some(motion.world_position_x),
+            Channel::PositionY => Some(motion.world_position_y),
+            Channel::PositionZ => Some(motion.world_position_z),
+            Channel::VelocityX => Some(motion.world_velocity_x),
+            Channel::VelocityY => Some(motion.world_velocity_y),
+            Channel::VelocityZ => Some(motion.world_velocity_z),
+            Channel::Speed => Some(motion.speed()),
+            Channel::GForceLateral => Some(motion.g_force_lateral),
+            Channel::GForceLongitudinal => Some(motion.g_force_longitudinal),
+            Channel::GForceVertical => Some(motion.g_force_vertical),
+            Channel::Yaw => Some(motion.yaw),
+            Channel::Pitch => Some(motion.pitch),
+            Channel::Roll => Some(motion.roll),
+            _ => None,
+        }
+    }
+
+    fn lap_data_value(&self, lap_data: &LapData) -> Option<f32> {
+        match self {
+            Channel::LapDistance => Some(lap_data.lap_distance),
+            Channel::TotalDistance => Some(lap_data.total_distance),
+            Channel::CurrentLapTime => Some(lap_data.current_lap_time),
+            Channel::LastLapTime => Some(lap_data.last_lap_time),
+            Channel::CurrentLapNum => Some(lap_data.current_lap_num as f32),
+            Channel::CarPosition => Some(lap_data.car_position as f32),
+            Channel::Sector => Some(lap_data.sector as f32),
+            _ => None,
+        }
+    }
+
+    fn car_status_value(&self, car_status: &CarStatus) -> Option<f32> {
+        match self {
+            Channel::FuelInTank => Some(car_status.fuel_in_tank),
+            Channel::FuelRemainingLaps => Some(car_status.fuel_remaining_laps),
+            Channel::ErsStoreEnergy => Some(car_status.ers_store_energy),
+            Channel::TyresAgeLaps => Some(car_status.tyres_age_laps as f32),
+            _ => None,
+        }
+    }
+}
+
+/// `CsvExporter` flattens frames into a CSV table with a row per car per session time.
+///
+/// The columns are `session_time`, `vehicle_index` and a column per `Channel`. The channels are
+/// sent in different packets at different rates. To align them on `session_time`, every row
+/// holds the most recent value of every channel at that session time. A cell is empty if no
+/// value has been received yet.
+pub struct CsvExporter<W: Write> {
+    writer: W,
+    channels: Vec<Channel>,
+    filter: Filter,
+    // The most recent value of every channel, indexed by vehicle index and channel.
+    values: Vec<Vec<Option<f32>>>,
+    // Session time of the values that haven't been written yet.
+    pending: Option<f32>,
+    header_written: bool,
+}
+
+impl<W: Write> CsvExporter<W> {
+    /// Create an exporter for the channels of Motion frames. See `Channel::MOTION`.
+    pub fn new(writer: W) -> Self {
+        CsvExporter {
+            writer,
+            channels: Channel::MOTION.to_vec(),
+            filter: Filter::default(),
+            values: vec![],
+            pending: None,
+            header_written: false,
+        }
+    }
+
+    /// Export these channels, in this order.
+    pub fn with_channels(mut self, channels: Vec<Channel>) -> Self {
+        self.channels = channels;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Process a single frame. Returns the number of rows written.
+    pub fn write_frame(&mut self, frame: &Frame) -> Result<usize, ExportError> {
+        if !self.filter.accepts(frame) {
+            return Ok(0);
+        }
+
+        let packet_type = &frame.header.packet_id;
+        if !self
+            .channels
+            .iter()
+            .any(|channel| channel.packet_type() == *packet_type)
+        {
+            return Ok(0);
+        }
+
+        let mut rows = 0;
+        let session_time = frame.header.session_time;
+        if self.pending.is_some() && self.pending != Some(session_time) {
+            rows = self.write_rows()?;
+        }
+        self.pending = Some(session_time);
+
+        match &frame.body {
+            Data::Motion(body) => self.update(&body.car_motion, Channel::motion_value),
+            Data::LapData(body) => self.update(&body.lap_data, Channel::lap_data_value),
+            Data::CarStatus(body) => self.update(&body.car_status, Channel::car_status_value),
+            _ => {}
+        }
+
+        Ok(rows)
+    }
+
+    /// Process all `frames`, like the `Frames` of a `telemetry::App`. Returns the number of rows
+    /// written.
+    pub fn export<I>(&mut self, frames: I) -> Result<usize, ExportError>
+    where
+        I: IntoIterator<Item = Frame>,
+    {
+        let mut rows = 0;
+        for frame in frames {
+            let written = self.write_frame(&frame)?;
+            if written > 0 {
+                rows += written;
+                self.writer.flush()?;
+            }
+        }
+        rows += self.write_rows()?;
+        self.writer.flush()?;
+
+        Ok(rows)
+    }
+
+    /// Write the rows of the most recent session time and return the underlying writer.
+    pub fn finish(mut self) -> Result<W, ExportError> {
+        self.write_rows()?;
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+
+    fn update<T, F>(&mut self, cars: &[T], value: F)
+    where
+        F: Fn(&Channel, &T) -> Option<f32>,
+    {
+        if self.values.len() < cars.len() {
+            self.values
+                .resize(cars.len(), vec![None; self.channels.len()]);
+        }
+
+        for (car, values) in cars.iter().zip(self.values.iter_mut()) {
+            for (channel, slot) in self.channels.iter().zip(values.iter_mut()) {
+                if let Some(value) = value(channel, car) {
+                    *slot = Some(value);
+                }
+            }
+        }
+    }
+
+    fn write_rows(&mut self) -> Result<usize, ExportError> {
+        let session_time = match self.pending.take() {
+            Some(session_time) => session_time,
+            None => return Ok(0),
+        };
+
+        if !self.header_written {
+            let mut header = vec!["session_time", "vehicle_index"];
+            header.extend(self.channels.iter().map(Channel::name));
+            writeln!(self.writer, "{}", header.join(","))?;
+            self.header_written = true;
+        }
+
+        let mut rows = 0;
+        for (index, values) in self.values.iter().enumerate() {
+            if !self.filter.accepts_car(index as u8) || values.iter().all(Option::is_none) {
+                continue;
+            }
+
+            let mut row = vec![session_time.to_string(), index.to_string()];
+            row.extend(values.iter().map(|value| match value {
+                Some(value) => value.to_string(),
+                None => String::new(),
+            }));
+            writeln!(self.writer, "{}", row.join(","))?;
+            rows += 1;
+        }
+
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::export::{
+        csv::{Channel, CsvExporter},
+        Filter,
+    };
+    use crate::fixtures::motion_frame;
+
+    #[test]
+    fn test_export() {
+        let mut exporter = CsvExporter::new(vec![])
+            .with_channels(vec![
+                Channel::PositionX,
+                Channel::Speed,
+                Channel::LapDistance,
+            ])
+            .with_filter(Filter::new().cars(vec![0, 3]));
+
+        let rows = exporter
+            .export(vec![motion_frame(0.5), motion_frame(1.0)])
+            .unwrap();
+        assert_eq!(rows, 4);
+
+        let output = String::from_utf8(exporter.finish().unwrap()).unwrap();
+        assert_eq!(
+            output,
+            "session_time,vehicle_index,position_x,speed,lap_distance\n\
+             0.5,0,0,0.5,\n\
+             0.5,3,3,0.5,\n\
+             1,0,0,1,\n\
+             1,3,3,1,\n"
+        );
+        assert_eq!(
+            Channel::from_name("g_force_lateral"),
+            Some(Channel::GForceLateral)
+        );
+    }
+}
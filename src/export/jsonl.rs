@@ -0,0 +1,237 @@
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{Map, Value};
+
+use crate::error::ExportError;
+use crate::export::Filter;
+use crate::frame::Frame;
+use crate::telemetry::ReceivedFrame;
+
+// Fields of the frame bodies that hold an entry per car, indexed by vehicle index.
+const PER_CAR_FIELDS: [&str; 4] = ["car_motion", "lap_data", "car_status", "participants"];
+
+/// `JsonLinesExporter` writes frames as [JSON Lines]: one JSON object per frame, separated by
+/// newlines.
+///
+/// Every object has a `header` and a `body`. The body is tagged with the kind of data, like
+/// `{"Motion": {...}}`. Every entry of a per-car list, like `car_motion` or `lap_data`, gets a
+/// `vehicle_index` field.
+///
+/// ```no_run
+/// use std::io;
+/// use std::net::UdpSocket;
+/// use std::thread;
+///
+/// use lole::export::{jsonl::JsonLinesExporter, Filter};
+/// use lole::frame::PacketType;
+///
+/// let socket = UdpSocket::bind("0.0.0.0:20777").unwrap();
+/// let mut app = lole::telemetry::App::new(socket);
+/// let frames = app.received_frames();
+/// thread::spawn(move || app.start());
+///
+/// let mut exporter = JsonLinesExporter::new(io::stdout())
+///     .with_filter(Filter::new().packet_types(vec![PacketType::LapData]))
+///     .with_fields(vec!["lap_distance".to_string(), "car_position".to_string()])
+///     .with_wall_clock(true);
+/// exporter.export_received(frames).unwrap();
+/// ```
+///
+/// [JSON Lines]: https://jsonlines.org
+pub struct JsonLinesExporter<W: Write> {
+    writer: W,
+    filter: Filter,
+    fields: Option<Vec<String>>,
+    wall_clock: bool,
+}
+
+impl<W: Write> JsonLinesExporter<W> {
+    pub fn new(writer: W) -> Self {
+        JsonLinesExporter {
+            writer,
+            filter: Filter::default(),
+            fields: None,
+            wall_clock: false,
+        }
+    }
+
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Only export these fields of the frame bodies. The header is always exported.
+    pub fn with_fields(mut self, fields: Vec<String>) -> Self {
+        self.fields = Some(fields);
+        self
+    }
+
+    /// Add a `received_at` field with the wall-clock time, in seconds since the Unix epoch, the
+    /// frame was received. Only frames written with `write_received_frame()` or
+    /// `export_received()` have a receive time.
+    pub fn with_wall_clock(mut self, wall_clock: bool) -> Self {
+        self.wall_clock = wall_clock;
+        self
+    }
+
+    /// Write a single frame. Returns whether the frame passed the filter and was written.
+    pub fn write_frame(&mut self, frame: &Frame) -> Result<bool, ExportError> {
+        self.write(frame, None)
+    }
+
+    /// Write a single frame together with the time it was received. See `write_frame()`.
+    pub fn write_received_frame(&mut self, received: &ReceivedFrame) -> Result<bool, ExportError> {
+        self.write(&received.frame, Some(received.received_at))
+    }
+
+    fn write(
+        &mut self,
+        frame: &Frame,
+        received_at: Option<SystemTime>,
+    ) -> Result<bool, ExportError> {
+        if !self.filter.accepts(frame) {
+            return Ok(false);
+        }
+
+        let mut value = serde_json::to_value(frame)?;
+        if let Some(Value::Object(body)) = value.get_mut("body") {
+            for data in body.values_mut() {
+                if let Value::Object(data) = data {
+                    self.filter_body(data);
+                }
+            }
+        }
+
+        if let (true, Some(received_at)) = (self.wall_clock, received_at) {
+            let received_at = received_at
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs_f64())
+                .unwrap_or(0.0);
+            if let Value::Object(object) = &mut value {
+                object.insert("received_at".to_string(), received_at.into());
+            }
+        }
+
+        serde_json::to_writer(&mut self.writer, &value)?;
+        self.writer.write_all(b"\n")?;
+
+        Ok(true)
+    }
+
+    /// Write all `frames`, like the `Frames` of a `telemetry::App`. Returns the number of frames
+    /// written.
+    pub fn export<I>(&mut self, frames: I) -> Result<usize, ExportError>
+    where
+        I: IntoIterator<Item = Frame>,
+    {
+        let mut written = 0;
+        for frame in frames {
+            if self.write_frame(&frame)? {
+                written += 1;
+                self.writer.flush()?;
+            }
+        }
+
+        Ok(written)
+    }
+
+    /// Write all `frames` together with the time they were received, like the
+    /// `ReceivedFrames` of a `telemetry::App`. Returns the number of frames written.
+    pub fn export_received<I>(&mut self, frames: I) -> Result<usize, ExportError>
+    where
+        I: IntoIterator<Item = ReceivedFrame>,
+    {
+        let mut written = 0;
+        for received in frames {
+            if self.write_received_frame(&received)? {
+                written += 1;
+                self.writer.flush()?;
+            }
+        }
+
+        Ok(written)
+    }
+
+    /// Return the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn filter_body(&self, data: &mut Map<String, Value>) {
+        for field in PER_CAR_FIELDS.iter() {
+            if let Some(Value::Array(cars)) = data.get_mut(*field) {
+                let entries = std::mem::take(cars);
+                *cars = entries
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(index, _)| self.filter.accepts_car(*index as u8))
+                    .map(|(index, mut entry)| {
+                        if let Value::Object(entry) = &mut entry {
+                            self.filter_fields(entry);
+                            entry.insert("vehicle_index".to_string(), index.into());
+                        }
+                        entry
+                    })
+                    .collect();
+            }
+        }
+
+        let fields = match &self.fields {
+            Some(fields) => fields,
+            None => return,
+        };
+        data.retain(|key, _| {
+            PER_CAR_FIELDS.contains(&key.as_str()) || fields.iter().any(|field| field == key)
+        });
+    }
+
+    fn filter_fields(&self, entry: &mut Map<String, Value>) {
+        if let Some(fields) = &self.fields {
+            entry.retain(|key, _| fields.iter().any(|field| field == key));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use serde_json::Value;
+
+    use crate::export::{jsonl::JsonLinesExporter, Filter};
+    use crate::fixtures::{lap_data, lap_data_frame};
+    use crate::telemetry::ReceivedFrame;
+
+    #[test]
+    fn test_export() {
+        let frame = lap_data_frame(12.5, vec![lap_data(1), lap_data(2), lap_data(3)]);
+        let received = ReceivedFrame {
+            received_at: UNIX_EPOCH + Duration::from_millis(1_600_000_000_250),
+            frame: frame.clone(),
+        };
+
+        let mut exporter = JsonLinesExporter::new(vec![])
+            .with_filter(Filter::new().cars(vec![1]))
+            .with_fields(vec!["car_position".to_string()])
+            .with_wall_clock(true);
+        assert_eq!(exporter.export_received(vec![received]).unwrap(), 1);
+        assert_eq!(exporter.export(vec![frame]).unwrap(), 1);
+
+        let output = String::from_utf8(exporter.into_inner()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let value: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(value["header"]["session_time"], 12.5);
+        assert_eq!(value["received_at"], 1_600_000_000.25);
+        assert_eq!(
+            value["body"]["LapData"]["lap_data"],
+            serde_json::json!([{"car_position": 2, "vehicle_index": 1}])
+        );
+
+        // The receive time of a frame without one is unknown.
+        let value: Value = serde_json::from_str(lines[1]).unwrap();
+        assert!(value.get("received_at").is_none());
+    }
+}
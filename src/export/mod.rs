@@ -0,0 +1,51 @@
+//! Export frames to formats other tools can read.
+pub mod csv;
+#[cfg(feature = "json")]
+pub mod jsonl;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+use crate::frame::{Frame, PacketType};
+
+/// `Filter` selects the frames and cars to export. By default everything is exported.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    packet_types: Option<Vec<PacketType>>,
+    cars: Option<Vec<u8>>,
+}
+
+impl Filter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only export frames of these packet types.
+    pub fn packet_types(mut self, packet_types: Vec<PacketType>) -> Self {
+        self.packet_types = Some(packet_types);
+        self
+    }
+
+    /// Only export the data of the cars with these vehicle indices.
+    pub fn cars(mut self, cars: Vec<u8>) -> Self {
+        self.cars = Some(cars);
+        self
+    }
+
+    /// Whether `frame` must be exported.
+    pub fn accepts(&self, frame: &Frame) -> bool {
+        match &self.packet_types {
+            Some(packet_types) => packet_types.contains(&frame.header.packet_id),
+            None => true,
+        }
+    }
+
+    /// Whether the data of the car with vehicle index `index` must be exported.
+    pub fn accepts_car(&self, index: u8) -> bool {
+        match &self.cars {
+            Some(cars) => cars.contains(&index),
+            None => true,
+        }
+    }
+}
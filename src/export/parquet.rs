@@ -0,0 +1,840 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ::parquet::arrow::ArrowWriter;
+use ::parquet::basic::Compression;
+use ::parquet::errors::ParquetError;
+use ::parquet::file::properties::WriterProperties;
+use arrow_array::{
+    ArrayRef, Float32Array, Int8Array, RecordBatch, StringArray, UInt16Array, UInt32Array,
+    UInt64Array, UInt8Array,
+};
+use arrow_schema::{DataType, Field, Schema};
+
+use crate::error::ExportError;
+use crate::export::Filter;
+use crate::frame::{
+    car_status::CarStatus,
+    event::{EventBody, EventDetails},
+    lap_data::LapData,
+    motion::CarMotion,
+    participants::Participant,
+    session::SessionBody,
+    Data, Frame,
+};
+use crate::math::decode_normalised;
+
+/// Number of rows of a table that are buffered before they're written as a row group.
+const ROW_GROUP_SIZE: usize = 10_000;
+
+/// `ParquetExporter` writes frames to [Apache Parquet] files, one file per packet type.
+///
+/// The files are named after the packet type, like `motion.parquet` or `lap_data.parquet`. Every
+/// row starts with the `session_uid`, `session_time` and `frame_identifier` of the frame.
+/// Packets with data of every car, like Motion and LapData, get a row per car with a
+/// `vehicle_index` column. The other columns are the fields of the frame types. The driver and
+/// team of a participant are written as their id in the F1 2020 API, other enums, like
+/// `PitStatus`, are written as strings. The direction vectors of the cars are decoded to floats
+/// between -1 and 1. Arrays of tyre values are split in a column per tyre.
+///
+/// Events get a column for every detail an event can have, like `vehicle_id` or `lap_time`.
+/// Details an event doesn't have are null.
+///
+/// Parquet files can only be read once they're complete, so call `finish()` when done.
+///
+/// ```no_run
+/// use lole::export::parquet::ParquetExporter;
+/// # let frames: Vec<lole::frame::Frame> = vec![];
+///
+/// let mut exporter = ParquetExporter::new("/tmp/race").unwrap();
+/// exporter.export(frames).unwrap();
+/// exporter.finish().unwrap();
+/// ```
+///
+/// [Apache Parquet]: https://parquet.apache.org
+pub struct ParquetExporter {
+    directory: PathBuf,
+    filter: Filter,
+    row_group_size: usize,
+    tables: BTreeMap<&'static str, Table>,
+}
+
+impl ParquetExporter {
+    /// Create an exporter that writes its files to `directory`. The directory is created if it
+    /// doesn't exist.
+    pub fn new<P: AsRef<Path>>(directory: P) -> Result<Self, ExportError> {
+        fs::create_dir_all(&directory)?;
+
+        Ok(ParquetExporter {
+            directory: directory.as_ref().to_path_buf(),
+            filter: Filter::default(),
+            row_group_size: ROW_GROUP_SIZE,
+            tables: BTreeMap::new(),
+        })
+    }
+
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Buffer this many rows of a table before writing them to disk.
+    pub fn with_row_group_size(mut self, row_group_size: usize) -> Self {
+        self.row_group_size = row_group_size.max(1);
+        self
+    }
+
+    /// Add a single frame to its table. Returns whether the frame passed the filter.
+    pub fn write_frame(&mut self, frame: &Frame) -> Result<bool, ExportError> {
+        if !self.filter.accepts(frame) {
+            return Ok(false);
+        }
+
+        let (name, rows) = match &frame.body {
+            Data::CarStatus(body) => (
+                "car_status",
+                self.per_car(frame, &body.car_status, car_status),
+            ),
+            Data::Event(body) => ("event", vec![event(frame, body)]),
+            Data::LapData(body) => ("lap_data", self.per_car(frame, &body.lap_data, lap_data)),
+            Data::Motion(body) => ("motion", self.per_car(frame, &body.car_motion, motion)),
+            Data::Participants(body) => (
+                "participants",
+                self.per_car(frame, &body.participants, participant),
+            ),
+            Data::Session(body) => ("session", vec![session(frame, body)]),
+        };
+
+        let path = self.directory.join(format!("{}.parquet", name));
+        let table = self.tables.entry(name).or_insert_with(|| Table::new(path));
+        for row in rows {
+            table.push(row);
+        }
+
+        if table.rows >= self.row_group_size {
+            table.flush()?;
+        }
+
+        Ok(true)
+    }
+
+    /// Add all `frames`, like the `Frames` of a `telemetry::App`. Returns the number of frames
+    /// that passed the filter.
+    pub fn export<I>(&mut self, frames: I) -> Result<usize, ExportError>
+    where
+        I: IntoIterator<Item = Frame>,
+    {
+        let mut written = 0;
+        for frame in frames {
+            if self.write_frame(&frame)? {
+                written += 1;
+            }
+        }
+
+        Ok(written)
+    }
+
+    /// Write the buffered rows and complete the files. Returns the paths of the files written.
+    pub fn finish(self) -> Result<Vec<PathBuf>, ExportError> {
+        let mut paths = vec![];
+        for (_, mut table) in self.tables {
+            table.flush()?;
+            if let Some(writer) = table.writer {
+                writer.close()?;
+                paths.push(table.path);
+            }
+        }
+
+        Ok(paths)
+    }
+
+    fn per_car<T, F>(&self, frame: &Frame, cars: &[T], columns: F) -> Vec<Row>
+    where
+        F: Fn(&mut Row, &T),
+    {
+        cars.iter()
+            .enumerate()
+            .filter(|(index, _)| self.filter.accepts_car(*index as u8))
+            .map(|(index, car)| {
+                let mut row = header(frame);
+                row.push(("vehicle_index", (index as u8).into()));
+                columns(&mut row, car);
+                row
+            })
+            .collect()
+    }
+}
+
+type Row = Vec<(&'static str, Cell)>;
+
+fn header(frame: &Frame) -> Row {
+    vec![
+        ("session_uid", frame.header.session_uid.into()),
+        ("session_time", frame.header.session_time.into()),
+        ("frame_identifier", frame.header.frame_identifier.into()),
+    ]
+}
+
+// Split the values of the 4 tyres in a column per tyre.
+fn tyres(row: &mut Row, names: [&'static str; 4], values: [u8; 4]) {
+    for (name, value) in names.iter().zip(values.iter()) {
+        row.push((name, (*value).into()));
+    }
+}
+
+fn motion(row: &mut Row, motion: &CarMotion) {
+    row.extend(vec![
+        ("world_position_x", motion.world_position_x.into()),
+        ("world_position_y", motion.world_position_y.into()),
+        ("world_position_z", motion.world_position_z.into()),
+        ("world_velocity_x", motion.world_velocity_x.into()),
+        ("world_velocity_y", motion.world_velocity_y.into()),
+        ("world_velocity_z", motion.world_velocity_z.into()),
+        (
+            "world_forward_direction_x",
+            decode_normalised(motion.world_forward_direction_x).into(),
+        ),
+        (
+            "world_forward_direction_y",
+            decode_normalised(motion.world_forward_direction_y).into(),
+        ),
+        (
+            "world_forward_direction_z",
+            decode_normalised(motion.world_forward_direction_z).into(),
+        ),
+        (
+            "world_right_direction_x",
+            decode_normalised(motion.world_right_direction_x).into(),
+        ),
+        (
+            "world_right_direction_y",
+            decode_normalised(motion.world_right_direction_y).into(),
+        ),
+        (
+            "world_right_direction_z",
+            decode_normalised(motion.world_right_direction_z).into(),
+        ),
+        ("g_force_lateral", motion.g_force_lateral.into()),
+        ("g_force_longitudinal", motion.g_force_longitudinal.into()),
+        ("g_force_vertical", motion.g_force_vertical.into()),
+        ("yaw", motion.yaw.into()),
+        ("pitch", motion.pitch.into()),
+        ("roll", motion.roll.into()),
+    ]);
+}
+
+fn lap_data(row: &mut Row, lap_data: &LapData) {
+    row.extend(vec![
+        ("last_lap_time", lap_data.last_lap_time.into()),
+        ("current_lap_time", lap_data.current_lap_time.into()),
+        ("sector_1_time_in_ms", lap_data.sector_1_time_in_ms.into()),
+        ("sector_2_time_in_ms", lap_data.sector_2_time_in_ms.into()),
+        ("best_lap_time", lap_data.best_lap_time.into()),
+        ("best_lap_num", lap_data.best_lap_num.into()),
+        (
+            "best_lap_sector_1_time_in_ms",
+            lap_data.best_lap_sector_1_time_in_ms.into(),
+        ),
+        (
+            "best_lap_sector_2_time_in_ms",
+            lap_data.best_lap_sector_2_time_in_ms.into(),
+        ),
+        (
+            "best_lap_sector_3_time_in_ms",
+            lap_data.best_lap_sector_3_time_in_ms.into(),
+        ),
+        (
+            "best_overall_sector_1_time_in_ms",
+            lap_data.best_overall_sector_1_time_in_ms.into(),
+        ),
+        (
+            "best_overall_sector_1_lap_num",
+            lap_data.best_overall_sector_1_lap_num.into(),
+        ),
+        (
+            "best_overall_sector_2_time_in_ms",
+            lap_data.best_overall_sector_2_time_in_ms.into(),
+        ),
+        (
+            "best_overall_sector_2_lap_num",
+            lap_data.best_overall_sector_2_lap_num.into(),
+        ),
+        (
+            "best_overall_sector_3_time_in_ms",
+            lap_data.best_overall_sector_3_time_in_ms.into(),
+        ),
+        (
+            "best_overall_sector_3_lap_num",
+            lap_data.best_overall_sector_3_lap_num.into(),
+        ),
+        ("lap_distance", lap_data.lap_distance.into()),
+        ("total_distance", lap_data.total_distance.into()),
+        ("safety_car_delta", lap_data.safety_car_delta.into()),
+        ("car_position", lap_data.car_position.into()),
+        ("current_lap_num", lap_data.current_lap_num.into()),
+        ("pit_status", format!("{:?}", lap_data.pit_status).into()),
+        ("sector", lap_data.sector.into()),
+        ("current_lap_invalid", lap_data.current_lap_invalid.into()),
+        ("penalties", lap_data.penalties.into()),
+        ("grid_position", lap_data.grid_position.into()),
+        (
+            "driver_status",
+            format!("{:?}", lap_data.driver_status).into(),
+        ),
+        (
+            "result_status",
+            format!("{:?}", lap_data.result_status).into(),
+        ),
+    ]);
+}
+
+fn car_status(row: &mut Row, car_status: &CarStatus) {
+    row.extend(vec![
+        ("traction_control", car_status.traction_control.into()),
+        ("anti_lock_brakes", car_status.anti_lock_brakes.into()),
+        ("fuel_mix", format!("{:?}", car_status.fuel_mix).into()),
+        ("front_brake_bias", car_status.front_brake_bias.into()),
+        ("pit_limiter_status", car_status.pit_limiter_status.into()),
+        ("fuel_in_tank", car_status.fuel_in_tank.into()),
+        ("fuel_capacity", car_status.fuel_capacity.into()),
+        ("fuel_remaining_laps", car_status.fuel_remaining_laps.into()),
+        ("max_rpm", car_status.max_rpm.into()),
+        ("idle_rpm", car_status.idle_rpm.into()),
+        ("max_gears", car_status.max_gears.into()),
+        ("drs_allowed", car_status.drs_allowed.into()),
+        (
+            "drs_activation_distance",
+            car_status.drs_activation_distance.into(),
+        ),
+    ]);
+    tyres(
+        row,
+        [
+            "tyres_wear_rear_left",
+            "tyres_wear_rear_right",
+            "tyres_wear_front_left",
+            "tyres_wear_front_right",
+        ],
+        car_status.tyres_wear,
+    );
+    row.extend(vec![
+        (
+            "actual_tyre_compound",
+            format!("{:?}", car_status.actual_tyre_compound).into(),
+        ),
+        (
+            "visual_tyre_compound",
+            format!("{:?}", car_status.visual_tyre_compound).into(),
+        ),
+        ("tyres_age_laps", car_status.tyres_age_laps.into()),
+    ]);
+    tyres(
+        row,
+        [
+            "tyres_damage_rear_left",
+            "tyres_damage_rear_right",
+            "tyres_damage_front_left",
+            "tyres_damage_front_right",
+        ],
+        car_status.tyres_damage,
+    );
+    row.extend(vec![
+        (
+            "front_left_wing_damage",
+            car_status.front_left_wing_damage.into(),
+        ),
+        (
+            "front_right_wing_damage",
+            car_status.front_right_wing_damage.into(),
+        ),
+        ("rear_wing_damage", car_status.rear_wing_damage.into()),
+        ("drs_fault", car_status.drs_fault.into()),
+        ("engine_damage", car_status.engine_damage.into()),
+        ("gear_box_damage", car_status.gear_box_damage.into()),
+        ("vehicle_fia_flags", car_status.vehicle_fia_flags.into()),
+        ("ers_store_energy", car_status.ers_store_energy.into()),
+        ("ers_deploy_mode", car_status.ers_deploy_mode.into()),
+        (
+            "ers_harvested_this_lap_mguk",
+            car_status.ers_harvested_this_lap_mguk.into(),
+        ),
+        (
+            "ers_harvested_this_lap_mguh",
+            car_status.ers_harvested_this_lap_mguh.into(),
+        ),
+        (
+            "ers_deployed_this_lap",
+            car_status.ers_deployed_this_lap.into(),
+        ),
+    ]);
+}
+
+fn participant(row: &mut Row, participant: &Participant) {
+    row.extend(vec![
+        ("ai_controlled", participant.ai_controlled.into()),
+        ("driver_id", u8::from(participant.driver_id).into()),
+        ("team_id", u8::from(participant.team).into()),
+        ("race_number", participant.race_number.into()),
+        ("nationality", participant.nationality.into()),
+        ("name", participant.name.clone().into()),
+        ("your_telemetry", participant.your_telemetry.into()),
+    ]);
+}
+
+fn event(frame: &Frame, event: &EventBody) -> Row {
+    let mut vehicle_id = None;
+    let mut other_vehicle_id = None;
+    let mut penalty_type = None;
+    let mut infringement_type = None;
+    let mut time = None;
+    let mut lap_number = None;
+    let mut places_gained = None;
+    let mut lap_time = None;
+    let mut speed = None;
+    match &event.details {
+        EventDetails::FastestLap {
+            vehicle_id: id,
+            lap_time: fastest,
+        } => {
+            vehicle_id = Some(*id);
+            lap_time = Some(*fastest);
+        }
+        EventDetails::Penalty {
+            penalty_type: penalty,
+            infringement_type: infringement,
+            vehicle_id: id,
+            other_vehicle_id: other_id,
+            time: seconds,
+            lap_number: lap,
+            places_gained: places,
+        } => {
+            vehicle_id = Some(*id);
+            // 255 means no other car was involved or the penalty doesn't come with a time.
+            other_vehicle_id = Some(*other_id).filter(|id| *id != 255);
+            penalty_type = Some(format!("{:?}", penalty));
+            infringement_type = Some(format!("{:?}", infringement));
+            time = Some(*seconds).filter(|seconds| *seconds != 255);
+            lap_number = Some(*lap);
+            places_gained = Some(*places);
+        }
+        EventDetails::SpeedTrap {
+            vehicle_id: id,
+            speed: top_speed,
+        } => {
+            vehicle_id = Some(*id);
+            speed = Some(*top_speed);
+        }
+        EventDetails::Retirement { vehicle_id: id }
+        | EventDetails::TeamMateInPits { vehicle_id: id } => vehicle_id = Some(*id),
+        _ => {}
+    }
+
+    let mut row = header(frame);
+    row.extend(vec![
+        ("code", event.code.clone().into()),
+        ("vehicle_id", vehicle_id.into()),
+        ("other_vehicle_id", other_vehicle_id.into()),
+        ("penalty_type", penalty_type.into()),
+        ("infringement_type", infringement_type.into()),
+        ("time", time.into()),
+        ("lap_number", lap_number.into()),
+        ("places_gained", places_gained.into()),
+        ("lap_time", lap_time.into()),
+        ("speed", speed.into()),
+    ]);
+    row
+}
+
+// Marshal zones and weather forecasts are lists and aren't exported.
+fn session(frame: &Frame, session: &SessionBody) -> Row {
+    let mut row = header(frame);
+    row.extend(vec![
+        ("weather", format!("{:?}", session.weather).into()),
+        ("track_temperature", session.track_temperature.into()),
+        ("air_temperature", session.air_temperature.into()),
+        ("total_laps", session.total_laps.into()),
+        ("track_length", session.track_length.into()),
+        ("session_type", format!("{:?}", session.session_type).into()),
+        ("track", format!("{:?}", session.track).into()),
+        ("formula", format!("{:?}", session.formula).into()),
+        ("session_time_left", session.session_time_left.into()),
+        ("session_duration", session.session_duration.into()),
+        ("pit_speed_limit", session.pit_speed_limit.into()),
+        ("game_paused", session.game_paused.into()),
+        ("is_spectating", session.is_spectating.into()),
+        ("spectator_car_index", session.spectator_car_index.into()),
+        (
+            "safety_car_status",
+            format!("{:?}", session.safety_car_status).into(),
+        ),
+        ("network_game", session.network_game.into()),
+    ]);
+    row
+}
+
+// A single value of a row. The value of a nullable column is an `Option`.
+struct Cell {
+    value: Value,
+    nullable: bool,
+}
+
+enum Value {
+    Float32(Option<f32>),
+    Int8(Option<i8>),
+    UInt8(Option<u8>),
+    UInt16(Option<u16>),
+    UInt32(Option<u32>),
+    UInt64(Option<u64>),
+    Utf8(Option<String>),
+}
+
+// Implement the conversion to a `Cell` of a value of `$type` and of an optional value of
+// `$type`, which goes in a nullable column.
+macro_rules! impl_cell {
+    ($type:ty, $variant:ident) => {
+        impl From<$type> for Cell {
+            fn from(value: $type) -> Self {
+                Cell {
+                    value: Value::$variant(Some(value)),
+                    nullable: false,
+                }
+            }
+        }
+
+        impl From<Option<$type>> for Cell {
+            fn from(value: Option<$type>) -> Self {
+                Cell {
+                    value: Value::$variant(value),
+                    nullable: true,
+                }
+            }
+        }
+    };
+}
+
+impl_cell!(f32, Float32);
+impl_cell!(i8, Int8);
+impl_cell!(u8, UInt8);
+impl_cell!(u16, UInt16);
+impl_cell!(u32, UInt32);
+impl_cell!(u64, UInt64);
+impl_cell!(String, Utf8);
+
+// The buffered values of a column.
+struct Column {
+    values: Values,
+    nullable: bool,
+}
+
+enum Values {
+    Float32(Vec<Option<f32>>),
+    Int8(Vec<Option<i8>>),
+    UInt8(Vec<Option<u8>>),
+    UInt16(Vec<Option<u16>>),
+    UInt32(Vec<Option<u32>>),
+    UInt64(Vec<Option<u64>>),
+    Utf8(Vec<Option<String>>),
+}
+
+impl Column {
+    // Create an empty column for values of the same type as `cell`.
+    fn new(cell: &Cell) -> Self {
+        let values = match cell.value {
+            Value::Float32(_) => Values::Float32(vec![]),
+            Value::Int8(_) => Values::Int8(vec![]),
+            Value::UInt8(_) => Values::UInt8(vec![]),
+            Value::UInt16(_) => Values::UInt16(vec![]),
+            Value::UInt32(_) => Values::UInt32(vec![]),
+            Value::UInt64(_) => Values::UInt64(vec![]),
+            Value::Utf8(_) => Values::Utf8(vec![]),
+        };
+
+        Column {
+            values,
+            nullable: cell.nullable,
+        }
+    }
+
+    fn push(&mut self, cell: Cell) {
+        match (&mut self.values, cell.value) {
+            (Values::Float32(values), Value::Float32(value)) => values.push(value),
+            (Values::Int8(values), Value::Int8(value)) => values.push(value),
+            (Values::UInt8(values), Value::UInt8(value)) => values.push(value),
+            (Values::UInt16(values), Value::UInt16(value)) => values.push(value),
+            (Values::UInt32(values), Value::UInt32(value)) => values.push(value),
+            (Values::UInt64(values), Value::UInt64(value)) => values.push(value),
+            (Values::Utf8(values), Value::Utf8(value)) => values.push(value),
+            _ => unreachable!("Rows of a table always have the same types."),
+        }
+    }
+
+    fn field(&self, name: &str) -> Field {
+        let data_type = match self.values {
+            Values::Float32(_) => DataType::Float32,
+            Values::Int8(_) => DataType::Int8,
+            Values::UInt8(_) => DataType::UInt8,
+            Values::UInt16(_) => DataType::UInt16,
+            Values::UInt32(_) => DataType::UInt32,
+            Values::UInt64(_) => DataType::UInt64,
+            Values::Utf8(_) => DataType::Utf8,
+        };
+
+        Field::new(name, data_type, self.nullable)
+    }
+
+    // Move the buffered values into an Arrow array.
+    fn take(&mut self) -> ArrayRef {
+        match &mut self.values {
+            Values::Float32(values) => Arc::new(Float32Array::from(std::mem::take(values))),
+            Values::Int8(values) => Arc::new(Int8Array::from(std::mem::take(values))),
+            Values::UInt8(values) => Arc::new(UInt8Array::from(std::mem::take(values))),
+            Values::UInt16(values) => Arc::new(UInt16Array::from(std::mem::take(values))),
+            Values::UInt32(values) => Arc::new(UInt32Array::from(std::mem::take(values))),
+            Values::UInt64(values) => Arc::new(UInt64Array::from(std::mem::take(values))),
+            Values::Utf8(values) => Arc::new(StringArray::from(std::mem::take(values))),
+        }
+    }
+}
+
+// A table is written to its own file. The file is created when the first row group is written.
+struct Table {
+    path: PathBuf,
+    columns: Vec<(&'static str, Column)>,
+    rows: usize,
+    writer: Option<ArrowWriter<File>>,
+}
+
+impl Table {
+    fn new(path: PathBuf) -> Self {
+        Table {
+            path,
+            columns: vec![],
+            rows: 0,
+            writer: None,
+        }
+    }
+
+    fn push(&mut self, row: Row) {
+        if self.columns.is_empty() {
+            self.columns = row
+                .iter()
+                .map(|(name, cell)| (*name, Column::new(cell)))
+                .collect();
+        }
+
+        for ((_, column), (_, cell)) in self.columns.iter_mut().zip(row) {
+            column.push(cell);
+        }
+        self.rows += 1;
+    }
+
+    fn flush(&mut self) -> Result<(), ExportError> {
+        if self.rows == 0 {
+            return Ok(());
+        }
+
+        let fields: Vec<Field> = self
+            .columns
+            .iter()
+            .map(|(name, column)| column.field(name))
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+        let arrays = self.columns.iter_mut().map(|(_, column)| column.take());
+        let batch =
+            RecordBatch::try_new(schema.clone(), arrays.collect()).map_err(ParquetError::from)?;
+        self.rows = 0;
+
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None => {
+                let properties = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+                let file = File::create(&self.path)?;
+                self.writer
+                    .get_or_insert(ArrowWriter::try_new(file, schema, Some(properties))?)
+            }
+        };
+        writer.write(&batch)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::path::Path;
+
+    use arrow_array::{Float32Array, RecordBatch, UInt8Array};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use crate::export::{parquet::ParquetExporter, Filter};
+    use crate::fixtures::{frame, motion_frame, participants_frame, temp_dir};
+    use crate::frame::{
+        event::{EventBody, EventDetails, InfringementType, PenaltyType},
+        participants::Driver,
+        Data, Frame,
+    };
+
+    fn read(path: &Path) -> Vec<RecordBatch> {
+        ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+    }
+
+    fn column<'a>(batch: &'a RecordBatch, name: &str) -> &'a UInt8Array {
+        batch
+            .column_by_name(name)
+            .unwrap()
+            .as_any()
+            .downcast_ref()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_export() {
+        let directory = temp_dir("parquet-export");
+        let mut exporter = ParquetExporter::new(&directory)
+            .unwrap()
+            .with_filter(Filter::new().cars(vec![0, 1]))
+            .with_row_group_size(3);
+
+        let frames: Vec<Frame> = (0..5)
+            .map(|frame_identifier| {
+                let mut frame = motion_frame(frame_identifier as f32 / 20.0);
+                frame.header.frame_identifier = frame_identifier;
+                if let Data::Motion(body) = &mut frame.body {
+                    // The car points backwards along the z axis.
+                    body.car_motion[0].world_forward_direction_z = (-32767_i16) as u16;
+                }
+                frame
+            })
+            .collect();
+        assert_eq!(exporter.export(frames).unwrap(), 5);
+
+        let paths = exporter.finish().unwrap();
+        assert_eq!(paths, vec![directory.join("motion.parquet")]);
+
+        let batches = read(&paths[0]);
+        assert_eq!(
+            batches.iter().map(|batch| batch.num_rows()).sum::<usize>(),
+            10
+        );
+
+        let schema = batches[0].schema();
+        assert_eq!(schema.fields().len(), 22);
+        assert_eq!(schema.field(3).name(), "vehicle_index");
+        assert_eq!(
+            schema.field_with_name("yaw").unwrap().data_type(),
+            &arrow_schema::DataType::Float32
+        );
+        assert_eq!(
+            schema
+                .field_with_name("world_forward_direction_z")
+                .unwrap()
+                .data_type(),
+            &arrow_schema::DataType::Float32
+        );
+        let forward_z: &Float32Array = batches[0]
+            .column_by_name("world_forward_direction_z")
+            .unwrap()
+            .as_any()
+            .downcast_ref()
+            .unwrap();
+        assert_eq!(forward_z.value(0), -1.0);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_typed_columns() {
+        let directory = temp_dir("parquet-typed-columns");
+        let mut exporter = ParquetExporter::new(&directory).unwrap();
+
+        let penalty = |session_time, other_vehicle_id| {
+            frame(
+                session_time,
+                Data::Event(EventBody {
+                    code: "PENA".to_string(),
+                    details: EventDetails::Penalty {
+                        penalty_type: PenaltyType::TimePenalty,
+                        infringement_type: InfringementType::SmallCollision,
+                        vehicle_id: 1,
+                        other_vehicle_id,
+                        time: 5,
+                        lap_number: 2,
+                        places_gained: 0,
+                    },
+                }),
+            )
+        };
+        let session_started = frame(
+            3.0,
+            Data::Event(EventBody {
+                code: "SSTA".to_string(),
+                details: EventDetails::SessionStarted,
+            }),
+        );
+        let frames = vec![
+            participants_frame(0.0),
+            penalty(1.0, 255),
+            penalty(2.0, 0),
+            session_started,
+        ];
+        assert_eq!(exporter.export(frames).unwrap(), 4);
+
+        let paths = exporter.finish().unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                directory.join("event.parquet"),
+                directory.join("participants.parquet"),
+            ]
+        );
+
+        let events = &read(&paths[0])[0];
+        assert_eq!(
+            column(events, "vehicle_id"),
+            &UInt8Array::from(vec![Some(1), Some(1), None])
+        );
+        assert_eq!(
+            column(events, "other_vehicle_id"),
+            &UInt8Array::from(vec![None, Some(0), None])
+        );
+        assert_eq!(
+            column(events, "time"),
+            &UInt8Array::from(vec![Some(5), Some(5), None])
+        );
+        assert!(events
+            .schema()
+            .field_with_name("lap_time")
+            .unwrap()
+            .is_nullable());
+
+        let participants = &read(&paths[1])[0];
+        assert_eq!(
+            column(participants, "driver_id"),
+            &UInt8Array::from(vec![
+                u8::from(Driver::LewisHemilton),
+                u8::from(Driver::ValterriBottas),
+            ])
+        );
+        assert_eq!(
+            column(participants, "team_id"),
+            &UInt8Array::from(vec![0, 0])
+        );
+        assert!(!participants
+            .schema()
+            .field_with_name("team_id")
+            .unwrap()
+            .is_nullable());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
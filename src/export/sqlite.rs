@@ -0,0 +1,857 @@
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use crate::error::ExportError;
+use crate::export::Filter;
+use crate::frame::{
+    event::{EventBody, EventDetails, PenaltyType},
+    motion::CarMotion,
+    participants::Participant,
+    session::SessionBody,
+    Data, Frame,
+};
+use crate::race::{event::RaceEvent, penalty::PenaltyRecord, CarId, Race};
+
+/// Number of frames stored in a single transaction. Committing a transaction per frame is slow,
+/// because SQLite syncs the database file to disk on every commit.
+const BATCH_SIZE: usize = 1_000;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    session_uid INTEGER PRIMARY KEY,
+    track TEXT,
+    session_type TEXT,
+    formula TEXT,
+    weather TEXT,
+    total_laps INTEGER,
+    track_length INTEGER,
+    air_temperature INTEGER,
+    track_temperature INTEGER,
+    network_game INTEGER
+);
+
+CREATE TABLE IF NOT EXISTS participants (
+    session_uid INTEGER NOT NULL REFERENCES sessions (session_uid),
+    vehicle_index INTEGER NOT NULL,
+    car_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    team TEXT NOT NULL,
+    driver TEXT NOT NULL,
+    race_number INTEGER NOT NULL,
+    nationality INTEGER NOT NULL,
+    ai_controlled INTEGER NOT NULL,
+    PRIMARY KEY (session_uid, vehicle_index)
+);
+
+CREATE TABLE IF NOT EXISTS laps (
+    session_uid INTEGER NOT NULL REFERENCES sessions (session_uid),
+    vehicle_index INTEGER NOT NULL,
+    lap_num INTEGER NOT NULL,
+    lap_time REAL NOT NULL,
+    sector_1_time_in_ms INTEGER NOT NULL,
+    sector_2_time_in_ms INTEGER NOT NULL,
+    sector_3_time_in_ms INTEGER NOT NULL,
+    invalid INTEGER NOT NULL,
+    car_position INTEGER NOT NULL,
+    PRIMARY KEY (session_uid, vehicle_index, lap_num)
+);
+
+CREATE TABLE IF NOT EXISTS motion_samples (
+    session_uid INTEGER NOT NULL REFERENCES sessions (session_uid),
+    session_time REAL NOT NULL,
+    frame_identifier INTEGER NOT NULL,
+    vehicle_index INTEGER NOT NULL,
+    world_position_x REAL NOT NULL,
+    world_position_y REAL NOT NULL,
+    world_position_z REAL NOT NULL,
+    world_velocity_x REAL NOT NULL,
+    world_velocity_y REAL NOT NULL,
+    world_velocity_z REAL NOT NULL,
+    world_forward_direction_x REAL NOT NULL,
+    world_forward_direction_y REAL NOT NULL,
+    world_forward_direction_z REAL NOT NULL,
+    world_right_direction_x REAL NOT NULL,
+    world_right_direction_y REAL NOT NULL,
+    world_right_direction_z REAL NOT NULL,
+    g_force_lateral REAL NOT NULL,
+    g_force_longitudinal REAL NOT NULL,
+    g_force_vertical REAL NOT NULL,
+    yaw REAL NOT NULL,
+    pitch REAL NOT NULL,
+    roll REAL NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS motion_samples_by_car
+    ON motion_samples (session_uid, vehicle_index, session_time);
+
+CREATE TABLE IF NOT EXISTS events (
+    session_uid INTEGER NOT NULL REFERENCES sessions (session_uid),
+    session_time REAL NOT NULL,
+    frame_identifier INTEGER NOT NULL,
+    code TEXT NOT NULL,
+    vehicle_index INTEGER,
+    details TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS penalties (
+    session_uid INTEGER NOT NULL REFERENCES sessions (session_uid),
+    session_time REAL NOT NULL,
+    vehicle_index INTEGER NOT NULL,
+    other_vehicle_index INTEGER,
+    penalty_type TEXT NOT NULL,
+    infringement_type TEXT NOT NULL,
+    time INTEGER,
+    lap_number INTEGER NOT NULL,
+    places_gained INTEGER NOT NULL,
+    served INTEGER
+);
+";
+
+/// `SqliteExporter` persists frames into a [SQLite] database, so sessions can be queried
+/// together.
+///
+/// The database has these tables, all keyed by `session_uid`:
+///
+/// * `sessions`: the track, session type, weather and so on of a session.
+/// * `participants`: the drivers of a session, by vehicle index. `car_id` is the `race::CarId` of
+///   the car.
+/// * `laps`: every lap completed by a car, see `RaceEvent::LapCompleted`. The game doesn't
+///   report the time of the third sector, it's the lap time minus the times of the first 2
+///   sectors.
+/// * `motion_samples`: position, velocity, direction, g-forces and rotation of every car of every
+///   Motion frame. The direction vectors are decoded to floats between -1 and 1.
+/// * `events`: every Event frame.
+/// * `penalties`: every penalty handed out, see `RaceEvent::Penalty`. `time` is null for
+///   penalties without a time. `served` tells whether a drive-through or stop-go penalty has been
+///   served, as tracked by the `PenaltySheet` of the car, and is null for other penalties.
+///
+/// The exporter feeds all frames, including the ones that don't pass the filter, to a
+/// `race::Race`. Laps and penalties are only stored once the Participants frame of the session
+/// has been received.
+///
+/// Frames are stored in batches of 1000 frames per transaction. `export()` commits the last
+/// batch, call `flush()` or `finish()` when storing frames with `write_frame()`. If a frame
+/// can't be stored, the frames of its batch that haven't been committed yet are discarded.
+///
+/// SQLite has no unsigned 64 bit integers. `session_uid` and `car_id` are stored as signed
+/// integers with the same bits, so they can be negative.
+///
+/// ```no_run
+/// use lole::export::sqlite::SqliteExporter;
+/// # let frames: Vec<lole::frame::Frame> = vec![];
+///
+/// let mut exporter = SqliteExporter::open("races.db").unwrap();
+/// exporter.export(frames).unwrap();
+/// exporter.finish().unwrap();
+/// ```
+///
+/// [SQLite]: https://sqlite.org
+pub struct SqliteExporter {
+    connection: Connection,
+    filter: Filter,
+    batch_size: usize,
+    // Number of frames stored in the open transaction.
+    pending: usize,
+    // Derives the completed laps and the penalties of the cars from the frames.
+    race: Race,
+}
+
+impl SqliteExporter {
+    /// Open or create the database at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ExportError> {
+        Self::new(Connection::open(path)?)
+    }
+
+    /// Create the tables in `connection`, unless they exist already.
+    pub fn new(connection: Connection) -> Result<Self, ExportError> {
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(SqliteExporter {
+            connection,
+            filter: Filter::default(),
+            batch_size: BATCH_SIZE,
+            pending: 0,
+            race: Race::new(),
+        })
+    }
+
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Store this many frames in a single transaction.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Store a single frame. Returns whether the frame passed the filter. The frame is committed
+    /// together with the other frames of its batch.
+    pub fn write_frame(&mut self, frame: &Frame) -> Result<bool, ExportError> {
+        let served = match frame.body {
+            Data::LapData(_) => self.served_penalties(),
+            _ => vec![],
+        };
+        let events = self.race.feed_frame(frame.clone());
+        if !self.filter.accepts(frame) {
+            return Ok(false);
+        }
+
+        if self.pending == 0 {
+            self.connection.execute_batch("BEGIN")?;
+        }
+        if let Err(error) = self.store(frame, &events, &served) {
+            // Don't leave the transaction open, or the next frame fails to begin a new one.
+            self.pending = 0;
+            if !self.connection.is_autocommit() {
+                self.connection.execute_batch("ROLLBACK")?;
+            }
+            return Err(error);
+        }
+
+        self.pending += 1;
+        if self.pending >= self.batch_size {
+            self.flush()?;
+        }
+
+        Ok(true)
+    }
+
+    // Store `frame` and the laps and penalties in the `events` derived from it. `served` holds
+    // the number of served drive-through and stop-go penalties of every car before the frame.
+    fn store(
+        &self,
+        frame: &Frame,
+        events: &[RaceEvent],
+        served: &[(u32, u32)],
+    ) -> Result<(), ExportError> {
+        let connection = &self.connection;
+        let session_uid = frame.header.session_uid as i64;
+        connection
+            .prepare_cached("INSERT OR IGNORE INTO sessions (session_uid) VALUES (?1)")?
+            .execute(params![session_uid])?;
+
+        match &frame.body {
+            Data::Session(body) => store_session(connection, session_uid, body)?,
+            Data::Participants(body) => {
+                for (index, participant) in body.participants.iter().enumerate() {
+                    if self.filter.accepts_car(index as u8) {
+                        store_participant(connection, session_uid, index as u8, participant)?;
+                    }
+                }
+            }
+            Data::LapData(body) => {
+                for event in events {
+                    if let RaceEvent::LapCompleted { car, .. } = event {
+                        if let Some(lap_data) = body.lap_data.get(car.index as usize) {
+                            if self.filter.accepts_car(car.index) {
+                                store_lap(connection, session_uid, event, lap_data.car_position)?;
+                            }
+                        }
+                    }
+                }
+
+                let now = self.served_penalties();
+                for (index, (before, after)) in served.iter().zip(now.iter()).enumerate() {
+                    if !self.filter.accepts_car(index as u8) {
+                        continue;
+                    }
+                    for _ in before.0..after.0 {
+                        serve_penalty(connection, session_uid, index, PenaltyType::DriveThrough)?;
+                    }
+                    for _ in before.1..after.1 {
+                        serve_penalty(connection, session_uid, index, PenaltyType::StopGo)?;
+                    }
+                }
+            }
+            Data::Motion(body) => {
+                for (index, motion) in body.car_motion.iter().enumerate() {
+                    if self.filter.accepts_car(index as u8) {
+                        store_motion(connection, frame, index as u8, motion)?;
+                    }
+                }
+            }
+            Data::Event(body) => {
+                store_event(connection, frame, body)?;
+                for event in events {
+                    if let RaceEvent::Penalty { car, .. } = event {
+                        if self.filter.accepts_car(car.index) {
+                            store_penalty(connection, frame, event)?;
+                        }
+                    }
+                }
+            }
+            Data::CarStatus(_) => {}
+        }
+
+        Ok(())
+    }
+
+    // Number of served drive-through and stop-go penalties of every car, by vehicle index.
+    fn served_penalties(&self) -> Vec<(u32, u32)> {
+        (0..self.race.participants.len() as u8)
+            .map(|index| {
+                let sheet = self
+                    .race
+                    .car(index)
+                    .and_then(|car| self.race.penalty_sheet(car.id()));
+                match sheet {
+                    Some(sheet) => (sheet.drive_throughs_served, sheet.stop_gos_served),
+                    None => (0, 0),
+                }
+            })
+            .collect()
+    }
+
+    /// Store all `frames`, like the `Frames` of a `telemetry::App`. Returns the number of frames
+    /// stored.
+    pub fn export<I>(&mut self, frames: I) -> Result<usize, ExportError>
+    where
+        I: IntoIterator<Item = Frame>,
+    {
+        let mut written = 0;
+        for frame in frames {
+            if self.write_frame(&frame)? {
+                written += 1;
+            }
+        }
+        self.flush()?;
+
+        Ok(written)
+    }
+
+    /// Commit the frames stored since the last commit.
+    pub fn flush(&mut self) -> Result<(), ExportError> {
+        if self.pending > 0 {
+            self.connection.execute_batch("COMMIT")?;
+            self.pending = 0;
+        }
+
+        Ok(())
+    }
+
+    /// Commit the frames stored since the last commit and return the connection to the
+    /// database.
+    pub fn finish(mut self) -> Result<Connection, ExportError> {
+        self.flush()?;
+
+        Ok(self.connection)
+    }
+}
+
+fn store_session(
+    connection: &Connection,
+    session_uid: i64,
+    session: &SessionBody,
+) -> Result<(), ExportError> {
+    connection
+        .prepare_cached(
+            "UPDATE sessions SET track = ?2, session_type = ?3, formula = ?4, weather = ?5,
+                total_laps = ?6, track_length = ?7, air_temperature = ?8,
+                track_temperature = ?9, network_game = ?10
+            WHERE session_uid = ?1",
+        )?
+        .execute(params![
+            session_uid,
+            format!("{:?}", session.track),
+            format!("{:?}", session.session_type),
+            format!("{:?}", session.formula),
+            format!("{:?}", session.weather),
+            session.total_laps,
+            session.track_length,
+            session.air_temperature,
+            session.track_temperature,
+            session.network_game,
+        ])?;
+
+    Ok(())
+}
+
+fn store_participant(
+    connection: &Connection,
+    session_uid: i64,
+    index: u8,
+    participant: &Participant,
+) -> Result<(), ExportError> {
+    connection
+        .prepare_cached(
+            "INSERT OR REPLACE INTO participants (session_uid, vehicle_index, car_id, name, team,
+                driver, race_number, nationality, ai_controlled)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?
+        .execute(params![
+            session_uid,
+            index,
+            u64::from(CarId::new(index, participant)) as i64,
+            participant.name,
+            format!("{:?}", participant.team),
+            format!("{:?}", participant.driver_id),
+            participant.race_number,
+            participant.nationality,
+            participant.ai_controlled,
+        ])?;
+
+    Ok(())
+}
+
+// Store the lap of a `RaceEvent::LapCompleted`. `car_position` is the position of the car at
+// the end of the lap.
+fn store_lap(
+    connection: &Connection,
+    session_uid: i64,
+    event: &RaceEvent,
+    car_position: u8,
+) -> Result<(), ExportError> {
+    let (car, lap, lap_time, sector_1_time_in_ms, sector_2_time_in_ms, invalid) = match event {
+        RaceEvent::LapCompleted {
+            car,
+            lap,
+            lap_time,
+            sector_1_time_in_ms,
+            sector_2_time_in_ms,
+            invalid,
+        } => (
+            car,
+            lap,
+            lap_time,
+            sector_1_time_in_ms,
+            sector_2_time_in_ms,
+            invalid,
+        ),
+        _ => return Ok(()),
+    };
+
+    connection
+        .prepare_cached(
+            "INSERT OR REPLACE INTO laps (session_uid, vehicle_index, lap_num, lap_time,
+                sector_1_time_in_ms, sector_2_time_in_ms, sector_3_time_in_ms, invalid,
+                car_position)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?
+        .execute(params![
+            session_uid,
+            car.index,
+            lap,
+            lap_time,
+            sector_1_time_in_ms,
+            sector_2_time_in_ms,
+            sector_3_time_in_ms(*lap_time, *sector_1_time_in_ms, *sector_2_time_in_ms),
+            invalid,
+            car_position,
+        ])?;
+
+    Ok(())
+}
+
+// The game doesn't report the time of the third sector. It's derived from the lap time.
+fn sector_3_time_in_ms(lap_time: f32, sector_1_time_in_ms: u16, sector_2_time_in_ms: u16) -> u32 {
+    let lap_time_in_ms = (lap_time * 1000.0).round() as u32;
+    lap_time_in_ms
+        .saturating_sub(u32::from(sector_1_time_in_ms))
+        .saturating_sub(u32::from(sector_2_time_in_ms))
+}
+
+fn store_motion(
+    connection: &Connection,
+    frame: &Frame,
+    index: u8,
+    motion: &CarMotion,
+) -> Result<(), ExportError> {
+    let forward = motion.forward();
+    let right = motion.right();
+    connection
+        .prepare_cached(
+            "INSERT INTO motion_samples (session_uid, session_time, frame_identifier,
+                vehicle_index, world_position_x, world_position_y, world_position_z,
+                world_velocity_x, world_velocity_y, world_velocity_z, world_forward_direction_x,
+                world_forward_direction_y, world_forward_direction_z, world_right_direction_x,
+                world_right_direction_y, world_right_direction_z, g_force_lateral,
+                g_force_longitudinal, g_force_vertical, yaw, pitch, roll)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
+                ?18, ?19, ?20, ?21, ?22)",
+        )?
+        .execute(params![
+            frame.header.session_uid as i64,
+            frame.header.session_time,
+            frame.header.frame_identifier,
+            index,
+            motion.world_position_x,
+            motion.world_position_y,
+            motion.world_position_z,
+            motion.world_velocity_x,
+            motion.world_velocity_y,
+            motion.world_velocity_z,
+            forward.x,
+            forward.y,
+            forward.z,
+            right.x,
+            right.y,
+            right.z,
+            motion.g_force_lateral,
+            motion.g_force_longitudinal,
+            motion.g_force_vertical,
+            motion.yaw,
+            motion.pitch,
+            motion.roll,
+        ])?;
+
+    Ok(())
+}
+
+fn store_event(
+    connection: &Connection,
+    frame: &Frame,
+    event: &EventBody,
+) -> Result<(), ExportError> {
+    let session_uid = frame.header.session_uid as i64;
+    let vehicle_index = match event.details {
+        EventDetails::FastestLap { vehicle_id, .. }
+        | EventDetails::Penalty { vehicle_id, .. }
+        | EventDetails::Retirement { vehicle_id }
+        | EventDetails::SpeedTrap { vehicle_id, .. }
+        | EventDetails::TeamMateInPits { vehicle_id } => Some(vehicle_id),
+        _ => None,
+    };
+
+    connection
+        .prepare_cached(
+            "INSERT INTO events (session_uid, session_time, frame_identifier, code,
+                vehicle_index, details)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?
+        .execute(params![
+            session_uid,
+            frame.header.session_time,
+            frame.header.frame_identifier,
+            event.code,
+            vehicle_index,
+            format!("{:?}", event.details),
+        ])?;
+
+    Ok(())
+}
+
+// Store the penalty of a `RaceEvent::Penalty`. Drive-through and stop-go penalties are stored as
+// not served yet.
+fn store_penalty(
+    connection: &Connection,
+    frame: &Frame,
+    event: &RaceEvent,
+) -> Result<(), ExportError> {
+    let (car, other_car, penalty_type, infringement_type, time, lap_number, places_gained) =
+        match event {
+            RaceEvent::Penalty {
+                car,
+                other_car,
+                penalty_type,
+                infringement_type,
+                time,
+                lap_number,
+                places_gained,
+            } => (
+                car,
+                other_car,
+                penalty_type,
+                infringement_type,
+                time,
+                lap_number,
+                places_gained,
+            ),
+            _ => return Ok(()),
+        };
+    let record = PenaltyRecord::new(
+        penalty_type.clone(),
+        infringement_type.clone(),
+        *time,
+        *lap_number,
+        *places_gained,
+    );
+    let served = match penalty_type {
+        PenaltyType::DriveThrough | PenaltyType::StopGo => Some(false),
+        _ => None,
+    };
+
+    connection
+        .prepare_cached(
+            "INSERT INTO penalties (session_uid, session_time, vehicle_index,
+                other_vehicle_index, penalty_type, infringement_type, time, lap_number,
+                places_gained, served)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        )?
+        .execute(params![
+            frame.header.session_uid as i64,
+            frame.header.session_time,
+            car.index,
+            other_car.as_ref().map(|car| car.index),
+            format!("{:?}", record.penalty_type),
+            format!("{:?}", record.infringement_type),
+            record.time,
+            record.lap,
+            record.places_gained,
+            served,
+        ])?;
+
+    Ok(())
+}
+
+// Mark the oldest penalty of `penalty_type` of the car at `index` that hasn't been served as
+// served. The `PenaltySheet` of the car only counts the served penalties.
+fn serve_penalty(
+    connection: &Connection,
+    session_uid: i64,
+    index: usize,
+    penalty_type: PenaltyType,
+) -> Result<(), ExportError> {
+    connection
+        .prepare_cached(
+            "UPDATE penalties SET served = 1 WHERE rowid = (
+                SELECT rowid FROM penalties
+                WHERE session_uid = ?1 AND vehicle_index = ?2 AND penalty_type = ?3
+                    AND served = 0
+                ORDER BY session_time LIMIT 1
+            )",
+        )?
+        .execute(params![
+            session_uid,
+            index as u8,
+            format!("{:?}", penalty_type)
+        ])?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+
+    use crate::export::sqlite::SqliteExporter;
+    use crate::fixtures::{self, lap_data, motion_frame, participants_frame};
+    use crate::frame::{
+        event::{EventBody, EventDetails, InfringementType, PenaltyType},
+        lap_data::{LapData, LapDataBody, PitStatus},
+        Data, Frame,
+    };
+
+    fn frame(session_time: f32, body: Data) -> Frame {
+        let mut frame = fixtures::frame(session_time, body);
+        frame.header.session_uid = u64::MAX;
+        frame
+    }
+
+    fn participants() -> Frame {
+        let mut frame = participants_frame(0.0);
+        frame.header.session_uid = u64::MAX;
+        frame
+    }
+
+    fn lap(current_lap_num: u8, last_lap_time: f32) -> LapData {
+        let mut lap_data = lap_data(1);
+        lap_data.current_lap_num = current_lap_num;
+        lap_data.last_lap_time = last_lap_time;
+        lap_data.sector_1_time_in_ms = 30_000;
+        lap_data.sector_2_time_in_ms = 31_000;
+        lap_data
+    }
+
+    fn lap_data_frame(session_time: f32, lap_data: LapData) -> Frame {
+        frame(
+            session_time,
+            Data::LapData(LapDataBody {
+                lap_data: vec![lap_data, lap_data],
+            }),
+        )
+    }
+
+    fn penalty(session_time: f32, penalty_type: PenaltyType, time: u8) -> Frame {
+        frame(
+            session_time,
+            Data::Event(EventBody {
+                code: "PENA".to_string(),
+                details: EventDetails::Penalty {
+                    penalty_type,
+                    infringement_type: InfringementType::CornerCuttingGainedTime,
+                    vehicle_id: 1,
+                    other_vehicle_id: 255,
+                    time,
+                    lap_number: 1,
+                    places_gained: 0,
+                },
+            }),
+        )
+    }
+
+    #[test]
+    fn test_export() {
+        let mut exporter = SqliteExporter::new(Connection::open_in_memory().unwrap()).unwrap();
+
+        let frames = vec![
+            participants(),
+            lap_data_frame(1.0, lap(1, 0.0)),
+            lap_data_frame(93.0, lap(2, 92.5)),
+            penalty(100.0, PenaltyType::TimePenalty, 5),
+        ];
+        assert_eq!(exporter.export(frames).unwrap(), 4);
+
+        let connection = exporter.finish().unwrap();
+        let (session_uid, lap_num, lap_time, sector_3_time_in_ms): (i64, u8, f32, u32) = connection
+            .query_row(
+                "SELECT session_uid, lap_num, lap_time, sector_3_time_in_ms FROM laps
+                    WHERE vehicle_index = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(session_uid as u64, u64::MAX);
+        assert_eq!(sector_3_time_in_ms, 31_500);
+        assert_eq!(lap_num, 1);
+        assert_eq!(lap_time, 92.5);
+
+        let (penalty_type, other_vehicle_index, time, served): (
+            String,
+            Option<u8>,
+            Option<u8>,
+            Option<bool>,
+        ) = connection
+            .query_row(
+                "SELECT penalty_type, other_vehicle_index, time, served FROM penalties",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(penalty_type, "TimePenalty");
+        assert_eq!(other_vehicle_index, None);
+        assert_eq!(time, Some(5));
+        assert_eq!(served, None);
+
+        let sessions: u32 = connection
+            .query_row("SELECT COUNT(*) FROM sessions", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(sessions, 1);
+    }
+
+    #[test]
+    fn test_laps_without_participants() {
+        let mut exporter = SqliteExporter::new(Connection::open_in_memory().unwrap()).unwrap();
+
+        let frames = vec![
+            lap_data_frame(1.0, lap(1, 0.0)),
+            lap_data_frame(93.0, lap(2, 92.5)),
+        ];
+        assert_eq!(exporter.export(frames).unwrap(), 2);
+
+        let connection = exporter.finish().unwrap();
+        let laps: u32 = connection
+            .query_row("SELECT COUNT(*) FROM laps", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(laps, 0);
+    }
+
+    #[test]
+    fn test_served_penalties() {
+        let mut exporter = SqliteExporter::new(Connection::open_in_memory().unwrap()).unwrap();
+
+        let mut pitting = lap(2, 92.5);
+        pitting.pit_status = PitStatus::Pitting;
+        let frames = vec![
+            participants(),
+            lap_data_frame(1.0, lap(2, 92.5)),
+            penalty(2.0, PenaltyType::DriveThrough, 255),
+            penalty(3.0, PenaltyType::DriveThrough, 255),
+            lap_data_frame(4.0, pitting),
+            lap_data_frame(5.0, lap(2, 92.5)),
+        ];
+        assert_eq!(exporter.export(frames).unwrap(), 6);
+
+        let connection = exporter.finish().unwrap();
+        let mut statement = connection
+            .prepare("SELECT time, served FROM penalties ORDER BY session_time")
+            .unwrap();
+        let penalties: Vec<(Option<u8>, Option<bool>)> = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(penalties, vec![(None, Some(true)), (None, Some(false))]);
+    }
+
+    #[test]
+    fn test_motion() {
+        let mut exporter = SqliteExporter::new(Connection::open_in_memory().unwrap()).unwrap();
+
+        let mut frame = motion_frame(1.0);
+        if let Data::Motion(body) = &mut frame.body {
+            // The car points backwards along the z axis.
+            body.car_motion[0].world_forward_direction_z = (-32767_i16) as u16;
+        }
+        assert_eq!(exporter.export(vec![frame]).unwrap(), 1);
+
+        let connection = exporter.finish().unwrap();
+        let forward_z: f32 = connection
+            .query_row(
+                "SELECT world_forward_direction_z FROM motion_samples WHERE vehicle_index = 0",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(forward_z, -1.0);
+    }
+
+    #[test]
+    fn test_batches() {
+        let mut exporter = SqliteExporter::new(Connection::open_in_memory().unwrap())
+            .unwrap()
+            .with_batch_size(2);
+
+        exporter.write_frame(&participants()).unwrap();
+        assert!(!exporter.connection.is_autocommit());
+        exporter
+            .write_frame(&lap_data_frame(1.0, lap(1, 0.0)))
+            .unwrap();
+        assert!(exporter.connection.is_autocommit());
+        exporter
+            .write_frame(&lap_data_frame(93.0, lap(2, 92.5)))
+            .unwrap();
+        assert!(!exporter.connection.is_autocommit());
+        exporter
+            .write_frame(&lap_data_frame(184.0, lap(3, 91.0)))
+            .unwrap();
+        assert!(exporter.connection.is_autocommit());
+
+        let connection = exporter.finish().unwrap();
+        assert!(connection.is_autocommit());
+        let laps: u32 = connection
+            .query_row("SELECT COUNT(*) FROM laps", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(laps, 4);
+    }
+
+    #[test]
+    fn test_rollback() {
+        let mut exporter = SqliteExporter::new(Connection::open_in_memory().unwrap()).unwrap();
+        exporter.write_frame(&participants()).unwrap();
+        exporter
+            .connection
+            .execute_batch("DROP TABLE motion_samples")
+            .unwrap();
+
+        // The batch is rolled back, the next frame starts a new batch.
+        assert!(exporter.write_frame(&motion_frame(1.0)).is_err());
+        assert!(exporter.connection.is_autocommit());
+        assert_eq!(exporter.pending, 0);
+        exporter
+            .write_frame(&lap_data_frame(2.0, lap(1, 0.0)))
+            .unwrap();
+
+        let connection = exporter.finish().unwrap();
+        let participants: u32 = connection
+            .query_row("SELECT COUNT(*) FROM participants", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(participants, 0);
+        let sessions: u32 = connection
+            .query_row("SELECT COUNT(*) FROM sessions", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(sessions, 1);
+    }
+}
@@ -0,0 +1,165 @@
+//! Frames and parts of frames shared by the tests.
+use crate::frame::{
+    car_status::{CarStatus, FuelMix, TyreCompound, VisualTyreCompound},
+    lap_data::{DriverStatus, LapData, LapDataBody, PitStatus, ResultStatus},
+    motion::{CarMotion, MotionBody, PlayerMotion},
+    participants::{Driver, Participant, ParticipantsBody, Team},
+    Data, Frame, Header, PacketType,
+};
+
+/// Create a frame of session 1 with `body`. The packet id matches the body.
+pub(crate) fn frame(session_time: f32, body: Data) -> Frame {
+    let packet_id = match body {
+        Data::CarStatus(_) => PacketType::CarStatus,
+        Data::Event(_) => PacketType::Event,
+        Data::LapData(_) => PacketType::LapData,
+        Data::Motion(_) => PacketType::Motion,
+        Data::Participants(_) => PacketType::Participants,
+        Data::Session(_) => PacketType::Session,
+    };
+
+    Frame {
+        header: Header {
+            packet_format: 2020,
+            game_major_version: 1,
+            game_minor_version: 18,
+            packet_version: 1,
+            packet_id,
+            session_uid: 1,
+            session_time,
+            frame_identifier: 0,
+            player_car_index: 0,
+            secondary_player_car_index: 255,
+        },
+        body,
+    }
+}
+
+/// Status of a car on new mediums with 30 kg of fuel.
+pub(crate) fn car_status() -> CarStatus {
+    CarStatus {
+        traction_control: 0,
+        anti_lock_brakes: 0,
+        fuel_mix: FuelMix::Standard,
+        front_brake_bias: 55,
+        pit_limiter_status: 0,
+        fuel_in_tank: 30.0,
+        fuel_capacity: 110.0,
+        fuel_remaining_laps: 10.0,
+        max_rpm: 13000,
+        idle_rpm: 4000,
+        max_gears: 8,
+        drs_allowed: 0,
+        drs_activation_distance: 0,
+        tyres_wear: [0; 4],
+        actual_tyre_compound: TyreCompound::C3,
+        visual_tyre_compound: VisualTyreCompound::Medium,
+        tyres_age_laps: 0,
+        tyres_damage: [0; 4],
+        front_left_wing_damage: 0,
+        front_right_wing_damage: 0,
+        rear_wing_damage: 0,
+        drs_fault: 0,
+        engine_damage: 0,
+        gear_box_damage: 0,
+        vehicle_fia_flags: 0,
+        ers_store_energy: 0.0,
+        ers_deploy_mode: 0,
+        ers_harvested_this_lap_mguk: 0.0,
+        ers_harvested_this_lap_mguh: 0.0,
+        ers_deployed_this_lap: 0.0,
+    }
+}
+
+/// Lap data of a car on its first lap, running in `car_position`.
+pub(crate) fn lap_data(car_position: u8) -> LapData {
+    LapData {
+        last_lap_time: 0.0,
+        current_lap_time: 0.0,
+        sector_1_time_in_ms: 0,
+        sector_2_time_in_ms: 0,
+        best_lap_time: 0.0,
+        best_lap_num: 0,
+        best_lap_sector_1_time_in_ms: 0,
+        best_lap_sector_2_time_in_ms: 0,
+        best_lap_sector_3_time_in_ms: 0,
+        best_overall_sector_1_time_in_ms: 0,
+        best_overall_sector_1_lap_num: 0,
+        best_overall_sector_2_time_in_ms: 0,
+        best_overall_sector_2_lap_num: 0,
+        best_overall_sector_3_time_in_ms: 0,
+        best_overall_sector_3_lap_num: 0,
+        lap_distance: 0.0,
+        total_distance: 0.0,
+        safety_car_delta: 0.0,
+        car_position,
+        current_lap_num: 1,
+        pit_status: PitStatus::None,
+        sector: 0,
+        current_lap_invalid: 0,
+        penalties: 0,
+        grid_position: car_position,
+        driver_status: DriverStatus::OnTrack,
+        result_status: ResultStatus::Active,
+    }
+}
+
+/// A Lap Data frame with `lap_data` of every car.
+pub(crate) fn lap_data_frame(session_time: f32, lap_data: Vec<LapData>) -> Frame {
+    frame(session_time, Data::LapData(LapDataBody { lap_data }))
+}
+
+pub(crate) fn participant(driver_id: Driver, team: Team, race_number: u8) -> Participant {
+    Participant {
+        ai_controlled: 1,
+        driver_id,
+        team,
+        race_number,
+        nationality: 0,
+        name: format!("{:?}", driver_id),
+        your_telemetry: 0,
+    }
+}
+
+/// A Participants frame with two Mercedes drivers: Lewis Hamilton at index 0 and Valtteri
+/// Bottas at index 1.
+pub(crate) fn participants_frame(session_time: f32) -> Frame {
+    frame(
+        session_time,
+        Data::Participants(ParticipantsBody {
+            number_of_active_cars: 2,
+            participants: vec![
+                participant(Driver::LewisHemilton, Team::Mercedes, 44),
+                participant(Driver::ValterriBottas, Team::Mercedes, 77),
+            ],
+        }),
+    )
+}
+
+/// A Motion frame in which car `n` is at x = `n` and drives `session_time` m/s along the z axis.
+pub(crate) fn motion_frame(session_time: f32) -> Frame {
+    let mut car_motion = [CarMotion::default(); 22];
+    for (index, motion) in car_motion.iter_mut().enumerate() {
+        motion.world_position_x = index as f32;
+        motion.world_velocity_z = session_time;
+    }
+
+    frame(
+        session_time,
+        Data::Motion(MotionBody {
+            car_motion,
+            player_motion: PlayerMotion::default(),
+        }),
+    )
+}
+
+/// Path of a directory in the temporary directory that is unique for every test run. The
+/// directory isn't created.
+#[cfg(feature = "parquet")]
+pub(crate) fn temp_dir(name: &str) -> std::path::PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    std::env::temp_dir().join(format!("lole-{}-{}-{}", name, std::process::id(), nanos))
+}
@@ -0,0 +1,165 @@
+use crate::error::{ErrorKind, ParseError};
+use std::convert::TryFrom;
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CarStatusBody {
+    /// Status of all cars in the race.
+    pub car_status: Vec<CarStatus>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CarStatus {
+    pub traction_control: u8,
+    pub anti_lock_brakes: u8,
+    pub fuel_mix: FuelMix,
+    /// Front brake bias in percentage.
+    pub front_brake_bias: u8,
+    pub pit_limiter_status: u8,
+    /// Fuel in tank in kilograms.
+    pub fuel_in_tank: f32,
+    /// Fuel capacity in kilograms.
+    pub fuel_capacity: f32,
+    /// Fuel remaining in terms of laps, as estimated by the game.
+    pub fuel_remaining_laps: f32,
+    pub max_rpm: u16,
+    pub idle_rpm: u16,
+    pub max_gears: u8,
+    /// 0 = not allowed, 1 = allowed, -1 = unknown.
+    pub drs_allowed: i8,
+    /// Distance in metres at which DRS will be available. 0 if not available.
+    pub drs_activation_distance: u16,
+    /// Tyre wear in percentage. The order is rear left, rear right, front left, front right.
+    pub tyres_wear: [u8; 4],
+    pub actual_tyre_compound: TyreCompound,
+    pub visual_tyre_compound: VisualTyreCompound,
+    /// Age in laps of the current set of tyres.
+    pub tyres_age_laps: u8,
+    /// Tyre damage in percentage. The order is rear left, rear right, front left, front right.
+    pub tyres_damage: [u8; 4],
+    pub front_left_wing_damage: u8,
+    pub front_right_wing_damage: u8,
+    pub rear_wing_damage: u8,
+    pub drs_fault: u8,
+    pub engine_damage: u8,
+    pub gear_box_damage: u8,
+    /// -1 = invalid/unknown, 0 = none, 1 = green, 2 = blue, 3 = yellow, 4 = red.
+    pub vehicle_fia_flags: i8,
+    /// ERS energy store in Joules.
+    pub ers_store_energy: f32,
+    pub ers_deploy_mode: u8,
+    pub ers_harvested_this_lap_mguk: f32,
+    pub ers_harvested_this_lap_mguh: f32,
+    pub ers_deployed_this_lap: f32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FuelMix {
+    Lean,
+    Standard,
+    Rich,
+    Max,
+}
+
+impl TryFrom<u8> for FuelMix {
+    type Error = ParseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Lean),
+            1 => Ok(Self::Standard),
+            2 => Ok(Self::Rich),
+            3 => Ok(Self::Max),
+            _ => Err(ParseError::new(ErrorKind::InvalidFuelMix(value))),
+        }
+    }
+}
+
+/// The compound of the tyres that are actually fitted to the car.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TyreCompound {
+    /// Compound isn't known, for example for cars that aren't active.
+    Unknown,
+    C5,
+    C4,
+    C3,
+    C2,
+    C1,
+    Inter,
+    Wet,
+    ClassicDry,
+    ClassicWet,
+    F2SuperSoft,
+    F2Soft,
+    F2Medium,
+    F2Hard,
+    F2Wet,
+}
+
+impl TryFrom<u8> for TyreCompound {
+    type Error = ParseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Unknown),
+            7 => Ok(Self::Inter),
+            8 => Ok(Self::Wet),
+            9 => Ok(Self::ClassicDry),
+            10 => Ok(Self::ClassicWet),
+            11 => Ok(Self::F2SuperSoft),
+            12 => Ok(Self::F2Soft),
+            13 => Ok(Self::F2Medium),
+            14 => Ok(Self::F2Hard),
+            15 => Ok(Self::F2Wet),
+            16 => Ok(Self::C5),
+            17 => Ok(Self::C4),
+            18 => Ok(Self::C3),
+            19 => Ok(Self::C2),
+            20 => Ok(Self::C1),
+            _ => Err(ParseError::new(ErrorKind::InvalidTyreCompound(value))),
+        }
+    }
+}
+
+/// The compound of the tyres as shown on the tyre walls. This can differ from the
+/// `TyreCompound` that is actually fitted.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VisualTyreCompound {
+    /// Compound isn't known, for example for cars that aren't active.
+    Unknown,
+    Soft,
+    Medium,
+    Hard,
+    Inter,
+    Wet,
+    F2Wet,
+    F2SuperSoft,
+    F2Soft,
+    F2Medium,
+    F2Hard,
+}
+
+impl TryFrom<u8> for VisualTyreCompound {
+    type Error = ParseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Unknown),
+            7 => Ok(Self::Inter),
+            8 => Ok(Self::Wet),
+            15 => Ok(Self::F2Wet),
+            16 => Ok(Self::Soft),
+            17 => Ok(Self::Medium),
+            18 => Ok(Self::Hard),
+            19 => Ok(Self::F2SuperSoft),
+            20 => Ok(Self::F2Soft),
+            21 => Ok(Self::F2Medium),
+            22 => Ok(Self::F2Hard),
+            _ => Err(ParseError::new(ErrorKind::InvalidVisualTyreCompound(value))),
+        }
+    }
+}
@@ -3,6 +3,7 @@ use std::convert::TryFrom;
 
 /// Body of a frame.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EventBody {
     /// Code indicating the event type.
     pub code: String,
@@ -13,6 +14,7 @@ pub struct EventBody {
 
 /// `Enum` representing the details of the `EventBody` frame.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EventDetails {
     /// Event generated when the chequered flag is waived.
     ChequeredFlag,
@@ -59,6 +61,7 @@ pub enum EventDetails {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PenaltyType {
     /// Penalty that forces driver to drive through the pit lane without stopping.
     DriveThrough,
@@ -113,6 +116,7 @@ impl TryFrom<u8> for PenaltyType {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum InfringementType {
     BlockingBySlowDriving,
     BlockingByWrongWayDriving,
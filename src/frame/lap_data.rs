@@ -0,0 +1,128 @@
+use crate::error::{ErrorKind, ParseError};
+use std::convert::TryFrom;
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LapDataBody {
+    /// Lap data of all cars on track.
+    pub lap_data: Vec<LapData>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LapData {
+    /// Last lap time in seconds.
+    pub last_lap_time: f32,
+    /// Current time around the lap in seconds.
+    pub current_lap_time: f32,
+    pub sector_1_time_in_ms: u16,
+    pub sector_2_time_in_ms: u16,
+    /// Best lap time of the session in seconds.
+    pub best_lap_time: f32,
+    pub best_lap_num: u8,
+    pub best_lap_sector_1_time_in_ms: u16,
+    pub best_lap_sector_2_time_in_ms: u16,
+    pub best_lap_sector_3_time_in_ms: u16,
+    pub best_overall_sector_1_time_in_ms: u16,
+    pub best_overall_sector_1_lap_num: u8,
+    pub best_overall_sector_2_time_in_ms: u16,
+    pub best_overall_sector_2_lap_num: u8,
+    pub best_overall_sector_3_time_in_ms: u16,
+    pub best_overall_sector_3_lap_num: u8,
+    /// Distance vehicle is around current lap in metres. Could be negative if line hasn't been
+    /// crossed yet.
+    pub lap_distance: f32,
+    /// Total distance travelled in session in metres. Could be negative if line hasn't been
+    /// crossed yet.
+    pub total_distance: f32,
+    /// Delta in seconds for safety car.
+    pub safety_car_delta: f32,
+    pub car_position: u8,
+    pub current_lap_num: u8,
+    pub pit_status: PitStatus,
+    /// Sector the car is in, starting at 0.
+    pub sector: u8,
+    pub current_lap_invalid: u8,
+    /// Accumulated time penalties in seconds to be added.
+    pub penalties: u8,
+    pub grid_position: u8,
+    pub driver_status: DriverStatus,
+    pub result_status: ResultStatus,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PitStatus {
+    None,
+    /// Car is driving through the pit lane.
+    Pitting,
+    /// Car is stationary in its pit box.
+    InPitArea,
+}
+
+impl TryFrom<u8> for PitStatus {
+    type Error = ParseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::None),
+            1 => Ok(Self::Pitting),
+            2 => Ok(Self::InPitArea),
+            _ => Err(ParseError::new(ErrorKind::InvalidPitStatus(value))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DriverStatus {
+    InGarage,
+    FlyingLap,
+    InLap,
+    OutLap,
+    OnTrack,
+}
+
+impl TryFrom<u8> for DriverStatus {
+    type Error = ParseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::InGarage),
+            1 => Ok(Self::FlyingLap),
+            2 => Ok(Self::InLap),
+            3 => Ok(Self::OutLap),
+            4 => Ok(Self::OnTrack),
+            _ => Err(ParseError::new(ErrorKind::InvalidDriverStatus(value))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResultStatus {
+    Invalid,
+    Inactive,
+    Active,
+    Finished,
+    Disqualified,
+    NotClassified,
+    Retired,
+}
+
+impl TryFrom<u8> for ResultStatus {
+    type Error = ParseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Invalid),
+            1 => Ok(Self::Inactive),
+            2 => Ok(Self::Active),
+            3 => Ok(Self::Finished),
+            4 => Ok(Self::Disqualified),
+            5 => Ok(Self::NotClassified),
+            6 => Ok(Self::Retired),
+            _ => Err(ParseError::new(ErrorKind::InvalidResultStatus(value))),
+        }
+    }
+}
@@ -1,19 +1,27 @@
+pub mod car_status;
 pub mod event;
+pub mod lap_data;
 pub mod motion;
 pub mod participants;
+pub mod session;
 
 use crate::error::{ErrorKind, ParseError};
 use crate::frame::participants::ParticipantsBody;
-use crate::frame::{event::EventBody, motion::MotionBody};
+use crate::frame::session::SessionBody;
+use crate::frame::{
+    car_status::CarStatusBody, event::EventBody, lap_data::LapDataBody, motion::MotionBody,
+};
 use std::convert::TryFrom;
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Frame {
     pub header: Header,
     pub body: Data,
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header {
     pub packet_format: u16,
     pub game_major_version: u8,
@@ -29,6 +37,7 @@ pub struct Header {
 
 /// The F1 2020 API defines 10 different types of packets.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PacketType {
     /// This packet contains physics data for all cars being driven.
     Motion,
@@ -81,9 +90,40 @@ impl TryFrom<u8> for PacketType {
     }
 }
 
+// `MotionBody` is much larger than the other bodies. Boxing it would only move the allocation
+// around, as every motion frame is consumed right away.
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Data {
+    CarStatus(CarStatusBody),
     Event(EventBody),
+    LapData(LapDataBody),
     Motion(MotionBody),
     Participants(ParticipantsBody),
+    Session(SessionBody),
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use crate::fixtures::{car_status, frame, lap_data, lap_data_frame, participants_frame};
+    use crate::frame::{car_status::CarStatusBody, Data, Frame};
+
+    #[test]
+    fn test_serde() {
+        let frames = vec![
+            participants_frame(0.0),
+            lap_data_frame(1.0, vec![lap_data(1), lap_data(2)]),
+            frame(
+                2.0,
+                Data::CarStatus(CarStatusBody {
+                    car_status: vec![car_status()],
+                }),
+            ),
+        ];
+        for frame in frames {
+            let json = serde_json::to_string(&frame).unwrap();
+            assert_eq!(serde_json::from_str::<Frame>(&json).unwrap(), frame);
+        }
+    }
 }
@@ -1,10 +1,48 @@
+use crate::math::Vec3;
+
 // This struct is around 1300 bytes. Is that maybe to large to implement `Clone()`?
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MotionBody {
     pub car_motion: [CarMotion; 22],
+    /// Extra motion data of the car of the player.
+    pub player_motion: PlayerMotion,
+}
+
+/// `PlayerMotion` is motion data that is only available for the car of the player. Values per
+/// wheel are ordered rear left, rear right, front left, front right.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerMotion {
+    pub suspension_position: [f32; 4],
+    pub suspension_velocity: [f32; 4],
+    pub suspension_acceleration: [f32; 4],
+    /// Speed of each wheel.
+    pub wheel_speed: [f32; 4],
+    /// Slip ratio of each wheel.
+    pub wheel_slip: [f32; 4],
+
+    /// Velocity in local space.
+    pub local_velocity_x: f32,
+    pub local_velocity_y: f32,
+    pub local_velocity_z: f32,
+
+    /// Angular velocity in radians per second.
+    pub angular_velocity_x: f32,
+    pub angular_velocity_y: f32,
+    pub angular_velocity_z: f32,
+
+    /// Angular acceleration in radians per second squared.
+    pub angular_acceleration_x: f32,
+    pub angular_acceleration_y: f32,
+    pub angular_acceleration_z: f32,
+
+    /// Current front wheels angle in radians.
+    pub front_wheels_angle: f32,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CarMotion {
     pub world_position_x: f32,
     pub world_position_y: f32,
@@ -32,6 +70,53 @@ pub struct CarMotion {
 }
 
 impl CarMotion {
+    /// Position of the car in the game world in metres.
+    pub fn position(&self) -> Vec3 {
+        Vec3::new(
+            self.world_position_x,
+            self.world_position_y,
+            self.world_position_z,
+        )
+    }
+
+    /// Velocity of the car in the game world in metres per second.
+    pub fn velocity(&self) -> Vec3 {
+        Vec3::new(
+            self.world_velocity_x,
+            self.world_velocity_y,
+            self.world_velocity_z,
+        )
+    }
+
+    /// Speed of the car in metres per second.
+    pub fn speed(&self) -> f32 {
+        self.velocity().length()
+    }
+
+    /// Unit vector pointing in the direction the nose of the car points to.
+    pub fn forward(&self) -> Vec3 {
+        Vec3::from_normalised(
+            self.world_forward_direction_x,
+            self.world_forward_direction_y,
+            self.world_forward_direction_z,
+        )
+    }
+
+    /// Unit vector pointing to the right hand side of the car.
+    pub fn right(&self) -> Vec3 {
+        Vec3::from_normalised(
+            self.world_right_direction_x,
+            self.world_right_direction_y,
+            self.world_right_direction_z,
+        )
+    }
+
+    /// Direction the car points to in the horizontal plane, in radians. See `Vec3::heading()`.
+    /// Unlike `yaw`, this is derived from the forward direction vector.
+    pub fn heading(&self) -> f32 {
+        self.forward().heading()
+    }
+
     // One could think: "Why not implementing the trait std::default::Default()?". Well, a
     // CarMotion filled with all zeroes doesn't make sense semantically.  That is why a `default()`
     // method isn't part of the public API. As trait methods are `pub` always, a custom `default()`
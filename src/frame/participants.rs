@@ -2,6 +2,7 @@ use crate::error::{ErrorKind, ParseError};
 use std::convert::TryFrom;
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ParticipantsBody {
     /// Code indicating the event type.
     pub number_of_active_cars: u8,
@@ -11,6 +12,7 @@ pub struct ParticipantsBody {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Participant {
     pub ai_controlled: u8,
     pub driver_id: Driver,
@@ -22,6 +24,7 @@ pub struct Participant {
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Team {
     Mercedes,
     Ferrari,
@@ -149,7 +152,73 @@ impl TryFrom<u8> for Team {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+/// The id of the team in the F1 2020 API.
+impl From<Team> for u8 {
+    fn from(team: Team) -> Self {
+        match team {
+            Team::Mercedes => 0,
+            Team::Ferrari => 1,
+            Team::RedBulRacing => 2,
+            Team::Williams => 3,
+            Team::RacingPoint => 4,
+            Team::Renault => 5,
+            Team::AlphaTauri => 6,
+            Team::Haas => 7,
+            Team::McLaren => 8,
+            Team::AlfaRomeo => 9,
+            Team::McLaren1988 => 10,
+            Team::McLaren1991 => 11,
+            Team::Williams1992 => 12,
+            Team::Ferrari1995 => 13,
+            Team::Williams1996 => 14,
+            Team::McLaren1998 => 15,
+            Team::Ferrari2002 => 16,
+            Team::Ferrari2004 => 17,
+            Team::Renault2006 => 18,
+            Team::Ferrari2007 => 19,
+            Team::McLaren2008 => 20,
+            Team::RedBull2010 => 21,
+            Team::Ferrari1976 => 22,
+            Team::ArtGrandPrix => 23,
+            Team::CamposVexatexRacing => 24,
+            Team::Calin => 25,
+            Team::CharouzRacingSystem => 26,
+            Team::Dams => 27,
+            Team::RussianTime => 28,
+            Team::MpMotorSport => 29,
+            Team::Pertamina => 30,
+            Team::McLaren1990 => 31,
+            Team::Trident => 32,
+            Team::BwtArden => 33,
+            Team::McLaren1976 => 34,
+            Team::Lotus1972 => 35,
+            Team::Ferrari1979 => 36,
+            Team::McLaren1982 => 37,
+            Team::Williams2003 => 38,
+            Team::Brawn2009 => 39,
+            Team::Lotus1978 => 40,
+            Team::F1GenericCar => 41,
+            Team::ArtGp19 => 42,
+            Team::Campos19 => 43,
+            Team::Calin19 => 44,
+            Team::SauberJuniorCharouz19 => 45,
+            Team::Dams19 => 46,
+            Team::UniVirtuosi19 => 47,
+            Team::MpMotorSport19 => 48,
+            Team::Prema19 => 49,
+            Team::Trident19 => 50,
+            Team::Arden19 => 51,
+            Team::Benetton1994 => 52,
+            Team::Benetton1995 => 53,
+            Team::Ferrari2000 => 54,
+            Team::Jordan1991 => 55,
+            Team::MyTeam => 255,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Driver {
     CarlozSains,
     DanillKvyat,
@@ -318,3 +387,89 @@ impl TryFrom<u8> for Driver {
         }
     }
 }
+
+/// The id of the driver in the F1 2020 API.
+impl From<Driver> for u8 {
+    fn from(driver: Driver) -> Self {
+        match driver {
+            Driver::CarlozSains => 0,
+            Driver::DanillKvyat => 1,
+            Driver::DanielRicciardo => 2,
+            Driver::KimiRaikkonen => 6,
+            Driver::LewisHemilton => 7,
+            Driver::MaxVerstappen => 9,
+            Driver::NicoHulkenberg => 10,
+            Driver::KevinMagnussen => 11,
+            Driver::RomainGrosjean => 12,
+            Driver::SebastianVettel => 13,
+            Driver::SergioPerez => 14,
+            Driver::ValterriBottas => 15,
+            Driver::EstebanOcon => 17,
+            Driver::LanceStroll => 19,
+            Driver::ArronBarnes => 20,
+            Driver::MartinGiles => 21,
+            Driver::AlexMurray => 22,
+            Driver::LucasRoth => 23,
+            Driver::IgorCorreia => 24,
+            Driver::SophieLevasseur => 25,
+            Driver::JonasSchiffer => 26,
+            Driver::AlainForest => 27,
+            Driver::JayLetournea => 28,
+            Driver::EstoSaari => 29,
+            Driver::YasarAtiyeh => 30,
+            Driver::CallistoCalabresi => 31,
+            Driver::NaotaIzum => 32,
+            Driver::HowardClarke => 33,
+            Driver::WilheimKaufmann => 34,
+            Driver::MarieLaursen => 35,
+            Driver::FlavioNieves => 36,
+            Driver::PeterBelousov => 37,
+            Driver::KlimekMichalksi => 38,
+            Driver::SantiagoMoreno => 39,
+            Driver::BenjaminCoppens => 40,
+            Driver::NoahVisser => 41,
+            Driver::GertWaldmuller => 42,
+            Driver::JulianQuesada => 43,
+            Driver::DanielJones => 44,
+            Driver::ArtemMarkelov => 45,
+            Driver::TadasukeMakino => 46,
+            Driver::SeanGeleal => 47,
+            Driver::NyckDeVreis => 48,
+            Driver::JackAitken => 49,
+            Driver::GeorgeRussel => 50,
+            Driver::MaximilianGunther => 51,
+            Driver::NireiFukuzumi => 52,
+            Driver::LucaGhiotto => 53,
+            Driver::LandoNorris => 54,
+            Driver::SergioSetteCamara => 55,
+            Driver::LouisDeletraz => 56,
+            Driver::AntonioFuoco => 57,
+            Driver::CharlesLeclerc => 58,
+            Driver::PierreGasly => 59,
+            Driver::AlexanderAlbon => 62,
+            Driver::NicholasLatifi => 63,
+            Driver::DorianBoccolacci => 64,
+            Driver::NikoKari => 65,
+            Driver::RobertoMerhi => 66,
+            Driver::ArjunMaini => 67,
+            Driver::AlessioLorandi => 68,
+            Driver::RubenMeijer => 69,
+            Driver::RashidNair => 70,
+            Driver::JackTremblay => 71,
+            Driver::AntonioGiovinazzi => 74,
+            Driver::RobertKubica => 75,
+            Driver::NobuharuMatsushita => 78,
+            Driver::NikitaMazepin => 79,
+            Driver::GuanyaZhou => 80,
+            Driver::MickSchumacher => 81,
+            Driver::CallumIlot => 82,
+            Driver::JuanManuelCorrea => 83,
+            Driver::JordanKing => 84,
+            Driver::MahaveerRaghunathan => 85,
+            Driver::TatianaCalderion => 86,
+            Driver::AnthoineHubert => 87,
+            Driver::GuilianoAlesi => 88,
+            Driver::RalphBoschung => 89,
+        }
+    }
+}
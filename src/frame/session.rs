@@ -0,0 +1,268 @@
+use crate::error::{ErrorKind, ParseError};
+use std::convert::TryFrom;
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionBody {
+    pub weather: Weather,
+    /// Track temperature in degrees Celsius.
+    pub track_temperature: i8,
+    /// Air temperature in degrees Celsius.
+    pub air_temperature: i8,
+    /// Total number of laps in this race.
+    pub total_laps: u8,
+    /// Track length in metres.
+    pub track_length: u16,
+    pub session_type: SessionType,
+    pub track: Circuit,
+    pub formula: Formula,
+    /// Time left in session in seconds.
+    pub session_time_left: u16,
+    /// Session duration in seconds.
+    pub session_duration: u16,
+    /// Pit speed limit in kilometres per hour.
+    pub pit_speed_limit: u8,
+    pub game_paused: u8,
+    pub is_spectating: u8,
+    /// Index of the car being spectated.
+    pub spectator_car_index: u8,
+    pub sli_pro_native_support: u8,
+    pub number_of_marshal_zones: u8,
+    pub marshal_zones: Vec<MarshalZone>,
+    pub safety_car_status: SafetyCarStatus,
+    pub network_game: u8,
+    pub number_of_weather_forecast_samples: u8,
+    pub weather_forecast_samples: Vec<WeatherForecastSample>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MarshalZone {
+    /// Fraction (0..1) of way through the lap the marshal zone starts.
+    pub zone_start: f32,
+    pub zone_flag: FiaFlag,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WeatherForecastSample {
+    pub session_type: SessionType,
+    /// Time in minutes the forecast is for.
+    pub time_offset: u8,
+    pub weather: Weather,
+    /// Track temperature in degrees Celsius.
+    pub track_temperature: i8,
+    /// Air temperature in degrees Celsius.
+    pub air_temperature: i8,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Weather {
+    Clear,
+    LightCloud,
+    Overcast,
+    LightRain,
+    HeavyRain,
+    Storm,
+}
+
+impl TryFrom<u8> for Weather {
+    type Error = ParseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Clear),
+            1 => Ok(Self::LightCloud),
+            2 => Ok(Self::Overcast),
+            3 => Ok(Self::LightRain),
+            4 => Ok(Self::HeavyRain),
+            5 => Ok(Self::Storm),
+            _ => Err(ParseError::new(ErrorKind::InvalidWeather(value))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SessionType {
+    Unknown,
+    Practice1,
+    Practice2,
+    Practice3,
+    ShortPractice,
+    Qualifying1,
+    Qualifying2,
+    Qualifying3,
+    ShortQualifying,
+    OneShotQualifying,
+    Race,
+    Race2,
+    TimeTrial,
+}
+
+impl TryFrom<u8> for SessionType {
+    type Error = ParseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Unknown),
+            1 => Ok(Self::Practice1),
+            2 => Ok(Self::Practice2),
+            3 => Ok(Self::Practice3),
+            4 => Ok(Self::ShortPractice),
+            5 => Ok(Self::Qualifying1),
+            6 => Ok(Self::Qualifying2),
+            7 => Ok(Self::Qualifying3),
+            8 => Ok(Self::ShortQualifying),
+            9 => Ok(Self::OneShotQualifying),
+            10 => Ok(Self::Race),
+            11 => Ok(Self::Race2),
+            12 => Ok(Self::TimeTrial),
+            _ => Err(ParseError::new(ErrorKind::InvalidSessionType(value))),
+        }
+    }
+}
+
+/// The circuit a session takes place at.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Circuit {
+    Unknown,
+    Melbourne,
+    PaulRicard,
+    Shanghai,
+    Sakhir,
+    Catalunya,
+    Monaco,
+    Montreal,
+    Silverstone,
+    Hockenheim,
+    Hungaroring,
+    Spa,
+    Monza,
+    Singapore,
+    Suzuka,
+    AbuDhabi,
+    Texas,
+    Brazil,
+    Austria,
+    Sochi,
+    Mexico,
+    Baku,
+    SakhirShort,
+    SilverstoneShort,
+    TexasShort,
+    SuzukaShort,
+    Hanoi,
+    Zandvoort,
+}
+
+impl TryFrom<i8> for Circuit {
+    type Error = ParseError;
+
+    fn try_from(value: i8) -> Result<Self, Self::Error> {
+        match value {
+            -1 => Ok(Self::Unknown),
+            0 => Ok(Self::Melbourne),
+            1 => Ok(Self::PaulRicard),
+            2 => Ok(Self::Shanghai),
+            3 => Ok(Self::Sakhir),
+            4 => Ok(Self::Catalunya),
+            5 => Ok(Self::Monaco),
+            6 => Ok(Self::Montreal),
+            7 => Ok(Self::Silverstone),
+            8 => Ok(Self::Hockenheim),
+            9 => Ok(Self::Hungaroring),
+            10 => Ok(Self::Spa),
+            11 => Ok(Self::Monza),
+            12 => Ok(Self::Singapore),
+            13 => Ok(Self::Suzuka),
+            14 => Ok(Self::AbuDhabi),
+            15 => Ok(Self::Texas),
+            16 => Ok(Self::Brazil),
+            17 => Ok(Self::Austria),
+            18 => Ok(Self::Sochi),
+            19 => Ok(Self::Mexico),
+            20 => Ok(Self::Baku),
+            21 => Ok(Self::SakhirShort),
+            22 => Ok(Self::SilverstoneShort),
+            23 => Ok(Self::TexasShort),
+            24 => Ok(Self::SuzukaShort),
+            25 => Ok(Self::Hanoi),
+            26 => Ok(Self::Zandvoort),
+            _ => Err(ParseError::new(ErrorKind::InvalidCircuit(value))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Formula {
+    F1Modern,
+    F1Classic,
+    F2,
+    F1Generic,
+}
+
+impl TryFrom<u8> for Formula {
+    type Error = ParseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::F1Modern),
+            1 => Ok(Self::F1Classic),
+            2 => Ok(Self::F2),
+            3 => Ok(Self::F1Generic),
+            _ => Err(ParseError::new(ErrorKind::InvalidFormula(value))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SafetyCarStatus {
+    NoSafetyCar,
+    FullSafetyCar,
+    VirtualSafetyCar,
+}
+
+impl TryFrom<u8> for SafetyCarStatus {
+    type Error = ParseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::NoSafetyCar),
+            1 => Ok(Self::FullSafetyCar),
+            2 => Ok(Self::VirtualSafetyCar),
+            _ => Err(ParseError::new(ErrorKind::InvalidSafetyCarStatus(value))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FiaFlag {
+    Unknown,
+    None,
+    Green,
+    Blue,
+    Yellow,
+    Red,
+}
+
+impl TryFrom<i8> for FiaFlag {
+    type Error = ParseError;
+
+    fn try_from(value: i8) -> Result<Self, Self::Error> {
+        match value {
+            -1 => Ok(Self::Unknown),
+            0 => Ok(Self::None),
+            1 => Ok(Self::Green),
+            2 => Ok(Self::Blue),
+            3 => Ok(Self::Yellow),
+            4 => Ok(Self::Red),
+            _ => Err(ParseError::new(ErrorKind::InvalidFiaFlag(value))),
+        }
+    }
+}
@@ -1,5 +1,11 @@
 pub mod error;
+pub mod export;
+#[cfg(test)]
+mod fixtures;
 pub mod frame;
+pub mod math;
 pub mod parser;
 pub mod race;
 pub mod telemetry;
+pub mod timing;
+pub mod track;
@@ -0,0 +1,159 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// `Vec3` is a vector in the 3D world of the game.
+///
+/// The game uses the x-axis and z-axis for the horizontal plane, and the y-axis for the
+/// elevation. This crate uses 3 coordinate frames:
+///
+/// * game world: `(x, y, z)` with y pointing up, as in the frames of the F1 2020 API and
+///   `Vec3`.
+/// * 2D map: `(x, z)`, the game world projected onto the horizontal plane. This is what
+///   `track::Point` uses.
+/// * 3D with elevation: `(x, z, y)`, a 2D map point extended with the elevation as third
+///   coordinate. This is what `SpatialLocation::coords` uses.
+///
+/// Use `Vec3::to_map()`, `Vec3::to_map_with_elevation()` and `Vec3::from_map_with_elevation()`
+/// to convert between these frames.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vec3 {
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        Vec3 { x, y, z }
+    }
+
+    /// Decode a direction vector that the F1 2020 API sends as normalised 16 bit integers.
+    pub fn from_normalised(x: u16, y: u16, z: u16) -> Self {
+        Vec3 {
+            x: decode_normalised(x),
+            y: decode_normalised(y),
+            z: decode_normalised(z),
+        }
+    }
+
+    /// Convert a point from the 3D with elevation frame `(x, z, y)` to the game world.
+    pub fn from_map_with_elevation(coords: (f32, f32, f32)) -> Self {
+        Vec3 {
+            x: coords.0,
+            y: coords.2,
+            z: coords.1,
+        }
+    }
+
+    /// Project the vector onto the 2D map `(x, z)`.
+    pub fn to_map(&self) -> (f32, f32) {
+        (self.x, self.z)
+    }
+
+    /// Convert the vector to the 3D with elevation frame `(x, z, y)`.
+    pub fn to_map_with_elevation(&self) -> (f32, f32, f32) {
+        (self.x, self.z, self.y)
+    }
+
+    pub fn length(&self) -> f32 {
+        self.dot(*self).sqrt()
+    }
+
+    /// Length of the vector projected onto the horizontal plane.
+    pub fn horizontal_length(&self) -> f32 {
+        (self.x * self.x + self.z * self.z).sqrt()
+    }
+
+    pub fn dot(&self, other: Vec3) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(&self, other: Vec3) -> Vec3 {
+        Vec3 {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    /// Return a vector with the same direction and a length of 1. A zero vector is returned
+    /// as is.
+    pub fn normalize(&self) -> Vec3 {
+        let length = self.length();
+        if length == 0.0 {
+            return *self;
+        }
+
+        *self * (1.0 / length)
+    }
+
+    /// Direction of the vector in the horizontal plane in radians. A heading of 0 points along
+    /// the z-axis, a heading of π/2 along the x-axis.
+    pub fn heading(&self) -> f32 {
+        self.x.atan2(self.z)
+    }
+
+    /// Angle in radians between the vectors in the horizontal plane, in the range -π to π.
+    pub fn horizontal_angle_to(&self, other: Vec3) -> f32 {
+        let cross = self.z * other.x - self.x * other.z;
+        let dot = self.z * other.z + self.x * other.x;
+        cross.atan2(dot)
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Vec3;
+
+    fn add(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<f32> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, factor: f32) -> Vec3 {
+        Vec3::new(self.x * factor, self.y * factor, self.z * factor)
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Vec3;
+
+    fn neg(self) -> Vec3 {
+        Vec3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+// The API sends the components of direction vectors as signed 16 bit integers. Divide
+// them by 32767 to get a float between -1 and 1.
+pub(crate) fn decode_normalised(value: u16) -> f32 {
+    value as i16 as f32 / 32767.0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::Vec3;
+
+    #[test]
+    fn test_from_normalised() {
+        let forward = Vec3::from_normalised(0, 0, 32767);
+        assert_eq!(forward, Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(forward.heading(), 0.0);
+
+        let backward = Vec3::from_normalised(0, 0, (-32767_i16) as u16);
+        assert_eq!(backward, Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(
+            forward.horizontal_angle_to(Vec3::new(1.0, 0.0, 0.0)),
+            std::f32::consts::FRAC_PI_2
+        );
+    }
+}
@@ -1,14 +1,20 @@
 use crate::frame::{
+    car_status::{CarStatus, CarStatusBody, FuelMix, TyreCompound, VisualTyreCompound},
     event::{EventBody, EventDetails, InfringementType, PenaltyType},
-    motion::{CarMotion, MotionBody},
+    lap_data::{DriverStatus, LapData, LapDataBody, PitStatus, ResultStatus},
+    motion::{CarMotion, MotionBody, PlayerMotion},
     participants::{Driver, Participant, ParticipantsBody, Team},
+    session::{
+        Circuit, FiaFlag, Formula, MarshalZone, SafetyCarStatus, SessionBody, SessionType, Weather,
+        WeatherForecastSample,
+    },
     Data, Frame, Header, PacketType,
 };
 use nom::{
     bytes::complete::take,
     combinator::map_res,
     error::VerboseError,
-    number::complete::{le_f32, le_u16, le_u32, le_u64, le_u8},
+    number::complete::{le_f32, le_i8, le_u16, le_u32, le_u64, le_u8},
     IResult,
 };
 use std::convert::TryFrom;
@@ -20,6 +26,14 @@ pub fn frame(input: &[u8]) -> IResult<&[u8], Frame, VerboseError<&[u8]>> {
             let (input, body) = event_body(input)?;
             (input, Data::Event(body))
         }
+        PacketType::CarStatus => {
+            let (input, body) = car_status_body(input)?;
+            (input, Data::CarStatus(body))
+        }
+        PacketType::LapData => {
+            let (input, body) = lap_data_body(input)?;
+            (input, Data::LapData(body))
+        }
         PacketType::Motion => {
             let (input, body) = motion_body(input)?;
             (input, Data::Motion(body))
@@ -28,6 +42,10 @@ pub fn frame(input: &[u8]) -> IResult<&[u8], Frame, VerboseError<&[u8]>> {
             let (input, body) = participants_body(input)?;
             (input, Data::Participants(body))
         }
+        PacketType::Session => {
+            let (input, body) = session_body(input)?;
+            (input, Data::Session(body))
+        }
         _ => {
             return Err(nom::Err::Error(nom::error::make_error(
                 input,
@@ -39,6 +57,19 @@ pub fn frame(input: &[u8]) -> IResult<&[u8], Frame, VerboseError<&[u8]>> {
     Ok((input, Frame { header, body }))
 }
 
+/// Whether `frame` parses packets of `packet_type`. Packets of other types are rejected.
+pub fn is_supported(packet_type: &PacketType) -> bool {
+    matches!(
+        packet_type,
+        PacketType::Event
+            | PacketType::CarStatus
+            | PacketType::LapData
+            | PacketType::Motion
+            | PacketType::Participants
+            | PacketType::Session
+    )
+}
+
 /// Parse byte slice as `Header`.
 pub fn header(input: &[u8]) -> IResult<&[u8], Header, VerboseError<&[u8]>> {
     let (input, packet_format) = le_u16(input)?;
@@ -143,16 +174,57 @@ pub fn event_body(input: &[u8]) -> IResult<&[u8], EventBody, VerboseError<&[u8]>
 pub fn motion_body(input: &[u8]) -> IResult<&[u8], MotionBody, VerboseError<&[u8]>> {
     let mut x = input;
     let mut car_motions: [CarMotion; 22] = [CarMotion::default(); 22];
-    for n in 0..22 {
+    for slot in car_motions.iter_mut() {
         let result = car_motion(x)?;
         x = result.0;
-        car_motions[n] = result.1;
+        *slot = result.1;
     }
+    let (x, player_motion) = player_motion(x)?;
 
     Ok((
-        input,
+        x,
         MotionBody {
             car_motion: car_motions,
+            player_motion,
+        },
+    ))
+}
+
+pub fn player_motion(input: &[u8]) -> IResult<&[u8], PlayerMotion, VerboseError<&[u8]>> {
+    let (input, suspension_position) = wheels_f32(input)?;
+    let (input, suspension_velocity) = wheels_f32(input)?;
+    let (input, suspension_acceleration) = wheels_f32(input)?;
+    let (input, wheel_speed) = wheels_f32(input)?;
+    let (input, wheel_slip) = wheels_f32(input)?;
+    let (input, local_velocity_x) = le_f32(input)?;
+    let (input, local_velocity_y) = le_f32(input)?;
+    let (input, local_velocity_z) = le_f32(input)?;
+    let (input, angular_velocity_x) = le_f32(input)?;
+    let (input, angular_velocity_y) = le_f32(input)?;
+    let (input, angular_velocity_z) = le_f32(input)?;
+    let (input, angular_acceleration_x) = le_f32(input)?;
+    let (input, angular_acceleration_y) = le_f32(input)?;
+    let (input, angular_acceleration_z) = le_f32(input)?;
+    let (input, front_wheels_angle) = le_f32(input)?;
+
+    Ok((
+        input,
+        PlayerMotion {
+            suspension_position,
+            suspension_velocity,
+            suspension_acceleration,
+            wheel_speed,
+            wheel_slip,
+            local_velocity_x,
+            local_velocity_y,
+            local_velocity_z,
+            angular_velocity_x,
+            angular_velocity_y,
+            angular_velocity_z,
+            angular_acceleration_x,
+            angular_acceleration_y,
+            angular_acceleration_z,
+            front_wheels_angle,
         },
     ))
 }
@@ -202,6 +274,192 @@ pub fn car_motion(input: &[u8]) -> IResult<&[u8], CarMotion, VerboseError<&[u8]>
     ))
 }
 
+pub fn lap_data_body(input: &[u8]) -> IResult<&[u8], LapDataBody, VerboseError<&[u8]>> {
+    let mut lap_data_of_cars: Vec<LapData> = Vec::with_capacity(22);
+    let mut input = input;
+    for _ in 0..22 {
+        let result = lap_data(input)?;
+        input = result.0;
+        lap_data_of_cars.push(result.1);
+    }
+
+    Ok((
+        input,
+        LapDataBody {
+            lap_data: lap_data_of_cars,
+        },
+    ))
+}
+
+pub fn lap_data(input: &[u8]) -> IResult<&[u8], LapData, VerboseError<&[u8]>> {
+    let (input, last_lap_time) = le_f32(input)?;
+    let (input, current_lap_time) = le_f32(input)?;
+    let (input, sector_1_time_in_ms) = le_u16(input)?;
+    let (input, sector_2_time_in_ms) = le_u16(input)?;
+    let (input, best_lap_time) = le_f32(input)?;
+    let (input, best_lap_num) = le_u8(input)?;
+    let (input, best_lap_sector_1_time_in_ms) = le_u16(input)?;
+    let (input, best_lap_sector_2_time_in_ms) = le_u16(input)?;
+    let (input, best_lap_sector_3_time_in_ms) = le_u16(input)?;
+    let (input, best_overall_sector_1_time_in_ms) = le_u16(input)?;
+    let (input, best_overall_sector_1_lap_num) = le_u8(input)?;
+    let (input, best_overall_sector_2_time_in_ms) = le_u16(input)?;
+    let (input, best_overall_sector_2_lap_num) = le_u8(input)?;
+    let (input, best_overall_sector_3_time_in_ms) = le_u16(input)?;
+    let (input, best_overall_sector_3_lap_num) = le_u8(input)?;
+    let (input, lap_distance) = le_f32(input)?;
+    let (input, total_distance) = le_f32(input)?;
+    let (input, safety_car_delta) = le_f32(input)?;
+    let (input, car_position) = le_u8(input)?;
+    let (input, current_lap_num) = le_u8(input)?;
+    let (input, pit_status) = map_res(le_u8, PitStatus::try_from)(input)?;
+    let (input, sector) = le_u8(input)?;
+    let (input, current_lap_invalid) = le_u8(input)?;
+    let (input, penalties) = le_u8(input)?;
+    let (input, grid_position) = le_u8(input)?;
+    let (input, driver_status) = map_res(le_u8, DriverStatus::try_from)(input)?;
+    let (input, result_status) = map_res(le_u8, ResultStatus::try_from)(input)?;
+
+    Ok((
+        input,
+        LapData {
+            last_lap_time,
+            current_lap_time,
+            sector_1_time_in_ms,
+            sector_2_time_in_ms,
+            best_lap_time,
+            best_lap_num,
+            best_lap_sector_1_time_in_ms,
+            best_lap_sector_2_time_in_ms,
+            best_lap_sector_3_time_in_ms,
+            best_overall_sector_1_time_in_ms,
+            best_overall_sector_1_lap_num,
+            best_overall_sector_2_time_in_ms,
+            best_overall_sector_2_lap_num,
+            best_overall_sector_3_time_in_ms,
+            best_overall_sector_3_lap_num,
+            lap_distance,
+            total_distance,
+            safety_car_delta,
+            car_position,
+            current_lap_num,
+            pit_status,
+            sector,
+            current_lap_invalid,
+            penalties,
+            grid_position,
+            driver_status,
+            result_status,
+        },
+    ))
+}
+
+pub fn car_status_body(input: &[u8]) -> IResult<&[u8], CarStatusBody, VerboseError<&[u8]>> {
+    let mut car_status_of_cars: Vec<CarStatus> = Vec::with_capacity(22);
+    let mut input = input;
+    for _ in 0..22 {
+        let result = car_status(input)?;
+        input = result.0;
+        car_status_of_cars.push(result.1);
+    }
+
+    Ok((
+        input,
+        CarStatusBody {
+            car_status: car_status_of_cars,
+        },
+    ))
+}
+
+pub fn car_status(input: &[u8]) -> IResult<&[u8], CarStatus, VerboseError<&[u8]>> {
+    let (input, traction_control) = le_u8(input)?;
+    let (input, anti_lock_brakes) = le_u8(input)?;
+    let (input, fuel_mix) = map_res(le_u8, FuelMix::try_from)(input)?;
+    let (input, front_brake_bias) = le_u8(input)?;
+    let (input, pit_limiter_status) = le_u8(input)?;
+    let (input, fuel_in_tank) = le_f32(input)?;
+    let (input, fuel_capacity) = le_f32(input)?;
+    let (input, fuel_remaining_laps) = le_f32(input)?;
+    let (input, max_rpm) = le_u16(input)?;
+    let (input, idle_rpm) = le_u16(input)?;
+    let (input, max_gears) = le_u8(input)?;
+    let (input, drs_allowed) = le_i8(input)?;
+    let (input, drs_activation_distance) = le_u16(input)?;
+    let (input, tyres_wear) = wheels(input)?;
+    let (input, actual_tyre_compound) = map_res(le_u8, TyreCompound::try_from)(input)?;
+    let (input, visual_tyre_compound) = map_res(le_u8, VisualTyreCompound::try_from)(input)?;
+    let (input, tyres_age_laps) = le_u8(input)?;
+    let (input, tyres_damage) = wheels(input)?;
+    let (input, front_left_wing_damage) = le_u8(input)?;
+    let (input, front_right_wing_damage) = le_u8(input)?;
+    let (input, rear_wing_damage) = le_u8(input)?;
+    let (input, drs_fault) = le_u8(input)?;
+    let (input, engine_damage) = le_u8(input)?;
+    let (input, gear_box_damage) = le_u8(input)?;
+    let (input, vehicle_fia_flags) = le_i8(input)?;
+    let (input, ers_store_energy) = le_f32(input)?;
+    let (input, ers_deploy_mode) = le_u8(input)?;
+    let (input, ers_harvested_this_lap_mguk) = le_f32(input)?;
+    let (input, ers_harvested_this_lap_mguh) = le_f32(input)?;
+    let (input, ers_deployed_this_lap) = le_f32(input)?;
+
+    Ok((
+        input,
+        CarStatus {
+            traction_control,
+            anti_lock_brakes,
+            fuel_mix,
+            front_brake_bias,
+            pit_limiter_status,
+            fuel_in_tank,
+            fuel_capacity,
+            fuel_remaining_laps,
+            max_rpm,
+            idle_rpm,
+            max_gears,
+            drs_allowed,
+            drs_activation_distance,
+            tyres_wear,
+            actual_tyre_compound,
+            visual_tyre_compound,
+            tyres_age_laps,
+            tyres_damage,
+            front_left_wing_damage,
+            front_right_wing_damage,
+            rear_wing_damage,
+            drs_fault,
+            engine_damage,
+            gear_box_damage,
+            vehicle_fia_flags,
+            ers_store_energy,
+            ers_deploy_mode,
+            ers_harvested_this_lap_mguk,
+            ers_harvested_this_lap_mguh,
+            ers_deployed_this_lap,
+        },
+    ))
+}
+
+// Parse a `u8` value for each of the 4 wheels of a car.
+fn wheels(input: &[u8]) -> IResult<&[u8], [u8; 4], VerboseError<&[u8]>> {
+    let (input, rear_left) = le_u8(input)?;
+    let (input, rear_right) = le_u8(input)?;
+    let (input, front_left) = le_u8(input)?;
+    let (input, front_right) = le_u8(input)?;
+
+    Ok((input, [rear_left, rear_right, front_left, front_right]))
+}
+
+// Parse a `f32` value for each of the 4 wheels of a car.
+fn wheels_f32(input: &[u8]) -> IResult<&[u8], [f32; 4], VerboseError<&[u8]>> {
+    let (input, rear_left) = le_f32(input)?;
+    let (input, rear_right) = le_f32(input)?;
+    let (input, front_left) = le_f32(input)?;
+    let (input, front_right) = le_f32(input)?;
+
+    Ok((input, [rear_left, rear_right, front_left, front_right]))
+}
+
 pub fn participants_body(input: &[u8]) -> IResult<&[u8], ParticipantsBody, VerboseError<&[u8]>> {
     let mut participants: Vec<Participant> = Vec::with_capacity(22);
     let (mut input, number_of_active_cars) = le_u8(input)?;
@@ -244,14 +502,128 @@ pub fn participant(input: &[u8]) -> IResult<&[u8], Participant, VerboseError<&[u
     ))
 }
 
+pub fn session_body(input: &[u8]) -> IResult<&[u8], SessionBody, VerboseError<&[u8]>> {
+    let (input, weather) = map_res(le_u8, Weather::try_from)(input)?;
+    let (input, track_temperature) = le_i8(input)?;
+    let (input, air_temperature) = le_i8(input)?;
+    let (input, total_laps) = le_u8(input)?;
+    let (input, track_length) = le_u16(input)?;
+    let (input, session_type) = map_res(le_u8, SessionType::try_from)(input)?;
+    let (input, track) = map_res(le_i8, Circuit::try_from)(input)?;
+    let (input, formula) = map_res(le_u8, Formula::try_from)(input)?;
+    let (input, session_time_left) = le_u16(input)?;
+    let (input, session_duration) = le_u16(input)?;
+    let (input, pit_speed_limit) = le_u8(input)?;
+    let (input, game_paused) = le_u8(input)?;
+    let (input, is_spectating) = le_u8(input)?;
+    let (input, spectator_car_index) = le_u8(input)?;
+    let (input, sli_pro_native_support) = le_u8(input)?;
+    let (input, number_of_marshal_zones) = le_u8(input)?;
+
+    // The packet always contains 21 marshal zones, but only the first
+    // `number_of_marshal_zones` are in use. The others might contain garbage.
+    let mut input = input;
+    let mut marshal_zones = Vec::with_capacity(number_of_marshal_zones as usize);
+    for n in 0..21 {
+        if n < number_of_marshal_zones {
+            let result = marshal_zone(input)?;
+            input = result.0;
+            marshal_zones.push(result.1);
+        } else {
+            input = take(5usize)(input)?.0;
+        }
+    }
+
+    let (input, safety_car_status) = map_res(le_u8, SafetyCarStatus::try_from)(input)?;
+    let (input, network_game) = le_u8(input)?;
+    let (mut input, number_of_weather_forecast_samples) = le_u8(input)?;
+
+    let mut weather_forecast_samples =
+        Vec::with_capacity(number_of_weather_forecast_samples as usize);
+    for n in 0..20 {
+        if n < number_of_weather_forecast_samples {
+            let result = weather_forecast_sample(input)?;
+            input = result.0;
+            weather_forecast_samples.push(result.1);
+        } else {
+            input = take(5usize)(input)?.0;
+        }
+    }
+
+    Ok((
+        input,
+        SessionBody {
+            weather,
+            track_temperature,
+            air_temperature,
+            total_laps,
+            track_length,
+            session_type,
+            track,
+            formula,
+            session_time_left,
+            session_duration,
+            pit_speed_limit,
+            game_paused,
+            is_spectating,
+            spectator_car_index,
+            sli_pro_native_support,
+            number_of_marshal_zones,
+            marshal_zones,
+            safety_car_status,
+            network_game,
+            number_of_weather_forecast_samples,
+            weather_forecast_samples,
+        },
+    ))
+}
+
+pub fn marshal_zone(input: &[u8]) -> IResult<&[u8], MarshalZone, VerboseError<&[u8]>> {
+    let (input, zone_start) = le_f32(input)?;
+    let (input, zone_flag) = map_res(le_i8, FiaFlag::try_from)(input)?;
+
+    Ok((
+        input,
+        MarshalZone {
+            zone_start,
+            zone_flag,
+        },
+    ))
+}
+
+pub fn weather_forecast_sample(
+    input: &[u8],
+) -> IResult<&[u8], WeatherForecastSample, VerboseError<&[u8]>> {
+    let (input, session_type) = map_res(le_u8, SessionType::try_from)(input)?;
+    let (input, time_offset) = le_u8(input)?;
+    let (input, weather) = map_res(le_u8, Weather::try_from)(input)?;
+    let (input, track_temperature) = le_i8(input)?;
+    let (input, air_temperature) = le_i8(input)?;
+
+    Ok((
+        input,
+        WeatherForecastSample {
+            session_type,
+            time_offset,
+            weather,
+            track_temperature,
+            air_temperature,
+        },
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::frame::{
         event::{EventBody, EventDetails, InfringementType, PenaltyType},
+        session::{
+            Circuit, FiaFlag, Formula, MarshalZone, SafetyCarStatus, SessionType, Weather,
+            WeatherForecastSample,
+        },
         Header, PacketType,
     };
 
-    use crate::parser::{event_body, header};
+    use crate::parser::{event_body, header, motion_body, session_body};
 
     #[test]
     fn test_parse_header() {
@@ -298,4 +670,80 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn test_parse_motion() {
+        // 22 cars of 60 bytes, followed by the 120 bytes of the player extra block.
+        let mut data = vec![0u8; 22 * 60 + 120];
+        let angular_velocity_y = 22 * 60 + 5 * 16 + 4 * 4;
+        data[angular_velocity_y..angular_velocity_y + 4].copy_from_slice(&1.5f32.to_le_bytes());
+        data.push(42);
+
+        let (input, body) = motion_body(&data).unwrap();
+        assert_eq!(input, &[42]);
+        assert_eq!(body.player_motion.angular_velocity_y, 1.5);
+    }
+
+    #[test]
+    fn test_parse_session() {
+        let mut data: Vec<u8> = vec![1, 30, 22, 53];
+        data.extend_from_slice(&5793u16.to_le_bytes());
+        data.extend_from_slice(&[10, 11, 0]);
+        data.extend_from_slice(&3600u16.to_le_bytes());
+        data.extend_from_slice(&7200u16.to_le_bytes());
+        data.extend_from_slice(&[80, 0, 0, 255, 0, 2]);
+
+        // 2 of the 21 marshal zones are in use, the others contain garbage.
+        data.extend_from_slice(&0.1f32.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(&0.5f32.to_le_bytes());
+        data.push(0);
+        data.extend_from_slice(&[0xff; 19 * 5]);
+
+        data.extend_from_slice(&[2, 0, 1]);
+
+        // 1 of the 20 weather forecast samples is in use.
+        data.extend_from_slice(&[10, 15, 3, 28, 20]);
+        data.extend_from_slice(&[0xff; 19 * 5]);
+        data.push(42);
+
+        let (input, body) = session_body(&data).unwrap();
+        assert_eq!(input, &[42]);
+        assert_eq!(body.weather, Weather::LightCloud);
+        assert_eq!(body.track_temperature, 30);
+        assert_eq!(body.air_temperature, 22);
+        assert_eq!(body.total_laps, 53);
+        assert_eq!(body.track_length, 5793);
+        assert_eq!(body.session_type, SessionType::Race);
+        assert_eq!(body.track, Circuit::Monza);
+        assert_eq!(body.formula, Formula::F1Modern);
+        assert_eq!(body.session_time_left, 3600);
+        assert_eq!(body.session_duration, 7200);
+        assert_eq!(body.pit_speed_limit, 80);
+        assert_eq!(body.spectator_car_index, 255);
+        assert_eq!(
+            body.marshal_zones,
+            vec![
+                MarshalZone {
+                    zone_start: 0.1,
+                    zone_flag: FiaFlag::Green,
+                },
+                MarshalZone {
+                    zone_start: 0.5,
+                    zone_flag: FiaFlag::None,
+                },
+            ]
+        );
+        assert_eq!(body.safety_car_status, SafetyCarStatus::VirtualSafetyCar);
+        assert_eq!(
+            body.weather_forecast_samples,
+            vec![WeatherForecastSample {
+                session_type: SessionType::Race,
+                time_offset: 15,
+                weather: Weather::LightRain,
+                track_temperature: 28,
+                air_temperature: 20,
+            }]
+        );
+    }
 }
@@ -0,0 +1,222 @@
+use crate::frame::motion::CarMotion;
+use crate::race::{Car, SpatialLocation, Timestamp};
+
+// Standard gravity in m/s².
+const G: f32 = 9.81;
+// Frames further apart in seconds aren't compared.
+const MAX_GAP: f32 = 0.5;
+// Deceleration in g derived from the change of velocity that counts as an impact. Braking
+// peaks around 6 g.
+const DECELERATION_THRESHOLD: f32 = 8.0;
+// Change of the horizontal g-force in g between 2 frames that counts as an impact.
+const G_FORCE_SPIKE: f32 = 4.0;
+// Maximum distance in metres between the centres of 2 cars touching each other.
+const CONTACT_DISTANCE: f32 = 6.0;
+// A car that moved further than expected in metres was reset to the track, for example
+// after a flashback or after being retired to the garage.
+const RESET_DISTANCE: f32 = 20.0;
+// Period in seconds after an impact in which a car can't have another impact. A single impact
+// spans several frames.
+const COOLDOWN: f32 = 1.0;
+
+/// `Impact` is a probable contact between 2 cars or between a car and a wall. The game doesn't
+/// report these, so they are derived from sudden changes in the motion of the cars.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Impact {
+    pub car: Car,
+    /// The other car involved in the contact, or `None` if `car` hit a wall.
+    pub other_car: Option<Car>,
+    /// Location of `car` at the moment of impact.
+    pub location: SpatialLocation,
+    /// Peak deceleration of the impact in g.
+    pub severity: f32,
+}
+
+// An impact of the car with vehicle index `index`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Detection {
+    pub index: usize,
+    pub other: Option<usize>,
+    pub severity: f32,
+}
+
+/// `ImpactDetector` compares consecutive Motion frames to detect impacts.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ImpactDetector {
+    previous: Vec<Option<(Timestamp, CarMotion)>>,
+    last_impact: Vec<Option<Timestamp>>,
+}
+
+impl ImpactDetector {
+    /// Compare the motion of every car with its motion in the previous frame. `motions` and
+    /// `active` are indexed by vehicle index. Only cars that are `active` can be the other car
+    /// of a contact; the others remain at a stale or default location.
+    pub fn update(
+        &mut self,
+        timestamp: Timestamp,
+        motions: &[CarMotion],
+        active: &[bool],
+    ) -> Vec<Detection> {
+        self.previous.resize(motions.len(), None);
+        self.last_impact.resize(motions.len(), None);
+
+        let mut detections: Vec<Detection> = vec![];
+        for (index, motion) in motions.iter().enumerate() {
+            let previous = self.previous[index].replace((timestamp, *motion));
+            let severity = match previous {
+                Some((time, previous)) => severity(timestamp - time, &previous, motion),
+                None => None,
+            };
+            let severity = match severity {
+                Some(severity) => severity,
+                None => continue,
+            };
+
+            match self.last_impact[index] {
+                Some(time) if timestamp - time < COOLDOWN => continue,
+                _ => self.last_impact[index] = Some(timestamp),
+            }
+
+            let other = closest_car(index, motions, active);
+            // Both cars of a contact usually register an impact. Report the contact once.
+            if let Some(other) = other {
+                if let Some(detection) = detections.iter_mut().find(|d| d.index == other) {
+                    detection.other = Some(index);
+                    detection.severity = detection.severity.max(severity);
+                    continue;
+                }
+            }
+
+            detections.push(Detection {
+                index,
+                other,
+                severity,
+            });
+        }
+
+        detections
+    }
+}
+
+// Return the peak deceleration in g if the change of motion of a car between 2 frames, `dt`
+// seconds apart, is an impact.
+fn severity(dt: f32, previous: &CarMotion, current: &CarMotion) -> Option<f32> {
+    if dt <= 0.0 || dt > MAX_GAP {
+        return None;
+    }
+
+    let expected = previous.speed().max(current.speed()) * dt;
+    if (current.position() - previous.position()).length() > expected + RESET_DISTANCE {
+        return None;
+    }
+
+    let deceleration = (current.velocity() - previous.velocity()).length() / dt / G;
+    let spike = ((current.g_force_lateral - previous.g_force_lateral).powi(2)
+        + (current.g_force_longitudinal - previous.g_force_longitudinal).powi(2))
+    .sqrt();
+
+    if deceleration < DECELERATION_THRESHOLD && spike < G_FORCE_SPIKE {
+        return None;
+    }
+
+    Some(deceleration.max(spike))
+}
+
+// Find the active car closest to the car with vehicle index `index`, if any is close enough to
+// touch.
+fn closest_car(index: usize, motions: &[CarMotion], active: &[bool]) -> Option<usize> {
+    let position = motions[index].position();
+    motions
+        .iter()
+        .enumerate()
+        .filter(|(other, _)| *other != index && active.get(*other) == Some(&true))
+        .map(|(other, motion)| (other, (motion.position() - position).length()))
+        .filter(|(_, distance)| *distance <= CONTACT_DISTANCE)
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(other, _)| other)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frame::motion::CarMotion;
+    use crate::race::contact::ImpactDetector;
+
+    fn motion(x: f32, z: f32, velocity_z: f32) -> CarMotion {
+        CarMotion {
+            world_position_x: x,
+            world_position_y: 0.0,
+            world_position_z: z,
+            world_velocity_x: 0.0,
+            world_velocity_y: 0.0,
+            world_velocity_z: velocity_z,
+            world_forward_direction_x: 0,
+            world_forward_direction_y: 0,
+            world_forward_direction_z: 32767,
+            world_right_direction_x: 32767,
+            world_right_direction_y: 0,
+            world_right_direction_z: 0,
+            g_force_lateral: 0.0,
+            g_force_longitudinal: 0.0,
+            g_force_vertical: 1.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            roll: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_impacts() {
+        let mut detector = ImpactDetector::default();
+        // 2 cars side by side and a car on its own, all driving 50 m/s.
+        let before = [
+            motion(0.0, 0.0, 50.0),
+            motion(2.0, 0.0, 50.0),
+            motion(500.0, 0.0, 50.0),
+        ];
+        let active = [true; 3];
+        assert!(detector.update(0.0, &before, &active).is_empty());
+
+        let mut braking = before;
+        for car in braking.iter_mut() {
+            car.world_position_z += 2.5;
+            car.world_velocity_z = 49.0;
+        }
+        assert!(detector.update(0.05, &braking, &active).is_empty());
+
+        // The first car hits the second car, the third car hits a wall.
+        let mut impact = braking;
+        impact[0].world_position_z += 2.0;
+        impact[0].world_velocity_z = 30.0;
+        impact[1].world_position_z += 2.5;
+        impact[2].world_position_z += 1.0;
+        impact[2].world_velocity_z = 0.0;
+        let detections = detector.update(0.1, &impact, &active);
+        assert_eq!(detections.len(), 2);
+        assert_eq!(detections[0].index, 0);
+        assert_eq!(detections[0].other, Some(1));
+        assert_eq!(detections[1].index, 2);
+        assert_eq!(detections[1].other, None);
+        assert!((detections[1].severity - 100.0).abs() < 1.0);
+
+        // The impact spans multiple frames, but is reported once.
+        assert!(detector.update(0.15, &impact, &active).is_empty());
+    }
+
+    #[test]
+    fn test_inactive_cars() {
+        let mut detector = ImpactDetector::default();
+        // The second car isn't active, it remains at its default location next to the first
+        // car.
+        let before = [motion(0.0, 0.0, 50.0), motion(2.0, 0.0, 0.0)];
+        let active = [true, false];
+        assert!(detector.update(0.0, &before, &active).is_empty());
+
+        let mut impact = before;
+        impact[0].world_velocity_z = 0.0;
+        let detections = detector.update(0.05, &impact, &active);
+        assert_eq!(detections.len(), 1);
+        assert_eq!(detections[0].index, 0);
+        assert_eq!(detections[0].other, None);
+    }
+}
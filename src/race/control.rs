@@ -0,0 +1,365 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use crate::frame::{
+    lap_data::LapData,
+    motion::{CarMotion, PlayerMotion},
+};
+use crate::math::Vec3;
+use crate::race::{Car, SpatialLocation, Timestamp};
+
+// Below this speed in m/s the direction of travel is too noisy to judge a car.
+const MIN_SPEED: f32 = 15.0;
+// Angle in radians between the direction of travel and the direction the car points to at which
+// a car slides.
+const SLIDE_ANGLE: f32 = 15.0 * PI / 180.0;
+// At this angle in radians a car travels sideways or backwards: it spins.
+const SPIN_ANGLE: f32 = FRAC_PI_2;
+// A sliding car rotating faster than this in radians per second spins.
+const SPIN_YAW_RATE: f32 = 3.0;
+// A slide or spin ended when the angle dropped below this angle in radians.
+const RECOVERY_ANGLE: f32 = 5.0 * PI / 180.0;
+// Metres a car must travel backwards along the lap without stopping to be driving the wrong
+// way.
+const WRONG_WAY_DISTANCE: f32 = 50.0;
+// A total distance that decreases more than this in metres between 2 frames is a reset of the
+// car, not a car driving backwards.
+const MAX_STEP: f32 = 100.0;
+
+/// `ControlLoss` is a moment a car wasn't under control of its driver.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ControlLoss {
+    pub car: Car,
+    pub kind: ControlLossKind,
+    pub location: SpatialLocation,
+    /// Distance in metres along the lap, or `None` if no Lap Data frame has been received yet.
+    pub lap_distance: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ControlLossKind {
+    /// The car spun.
+    Spin,
+    /// The car slid, but the driver caught it.
+    Slide {
+        /// Peak angle in radians between the direction of travel and the direction the car
+        /// points to.
+        slip_angle: f32,
+    },
+    /// The car travels in the wrong direction along the track.
+    WrongWay,
+}
+
+// A loss of control of the car with vehicle index `index`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Detection {
+    pub index: usize,
+    pub kind: ControlLossKind,
+    pub timestamp: Timestamp,
+    pub position: Vec3,
+    pub lap_distance: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Episode {
+    Slide {
+        slip_angle: f32,
+        timestamp: Timestamp,
+        position: Vec3,
+    },
+    Spin,
+}
+
+#[derive(Debug, Clone, Default)]
+struct CarState {
+    yaw: Option<(Timestamp, f32)>,
+    position: Option<(Timestamp, Vec3)>,
+    episode: Option<Episode>,
+    lap_distance: Option<f32>,
+    total_distance: Option<f32>,
+    backwards: f32,
+    wrong_way: bool,
+}
+
+/// `ControlDetector` detects spins, slides and cars driving the wrong way.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ControlDetector {
+    cars: Vec<CarState>,
+}
+
+impl ControlDetector {
+    /// Detect spins and slides in a Motion frame. `motions` is indexed by vehicle index. The
+    /// angular velocity of `player_motion` is used for the car with index `player`.
+    pub fn update_motion(
+        &mut self,
+        timestamp: Timestamp,
+        motions: &[CarMotion],
+        player: usize,
+        player_motion: &PlayerMotion,
+    ) -> Vec<Detection> {
+        if self.cars.len() < motions.len() {
+            self.cars.resize(motions.len(), CarState::default());
+        }
+
+        let mut detections = vec![];
+        for (index, (motion, state)) in motions.iter().zip(self.cars.iter_mut()).enumerate() {
+            let previous_yaw = state.yaw.replace((timestamp, motion.yaw));
+            let yaw_rate = if index == player {
+                Some(player_motion.angular_velocity_y)
+            } else {
+                match previous_yaw {
+                    Some((time, yaw)) if timestamp > time => {
+                        Some(wrap(motion.yaw - yaw) / (timestamp - time))
+                    }
+                    _ => None,
+                }
+            };
+
+            let position = motion.position();
+            state.position = Some((timestamp, position));
+
+            let slip_angle = motion
+                .forward()
+                .horizontal_angle_to(motion.velocity())
+                .abs();
+            let in_control = motion.speed() < MIN_SPEED || slip_angle < RECOVERY_ANGLE;
+            let spins = slip_angle >= SPIN_ANGLE
+                || (slip_angle >= SLIDE_ANGLE
+                    && yaw_rate.map(f32::abs).unwrap_or(0.0) >= SPIN_YAW_RATE);
+
+            let lap_distance = state.lap_distance;
+            let detection = |kind| Detection {
+                index,
+                kind,
+                timestamp,
+                position,
+                lap_distance,
+            };
+
+            match state.episode {
+                Some(Episode::Spin) if in_control => state.episode = None,
+                Some(Episode::Spin) => {}
+                Some(Episode::Slide {
+                    slip_angle: peak,
+                    timestamp,
+                    position,
+                }) if in_control => {
+                    detections.push(Detection {
+                        index,
+                        kind: ControlLossKind::Slide { slip_angle: peak },
+                        timestamp,
+                        position,
+                        lap_distance,
+                    });
+                    state.episode = None;
+                }
+                _ if motion.speed() < MIN_SPEED => {}
+                _ if spins => {
+                    detections.push(detection(ControlLossKind::Spin));
+                    state.episode = Some(Episode::Spin);
+                }
+                Some(Episode::Slide {
+                    slip_angle: peak, ..
+                }) if slip_angle <= peak => {}
+                _ if slip_angle >= SLIDE_ANGLE || state.episode.is_some() => {
+                    state.episode = Some(Episode::Slide {
+                        slip_angle,
+                        timestamp,
+                        position,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        detections
+    }
+
+    /// Detect cars driving the wrong way in a Lap Data frame. `lap_data` is indexed by vehicle
+    /// index. The total distance is compared rather than the lap distance, as it doesn't wrap
+    /// around at the start line.
+    pub fn update_lap_data(&mut self, lap_data: &[LapData]) -> Vec<Detection> {
+        if self.cars.len() < lap_data.len() {
+            self.cars.resize(lap_data.len(), CarState::default());
+        }
+
+        let mut detections = vec![];
+        for (index, (lap, state)) in lap_data.iter().zip(self.cars.iter_mut()).enumerate() {
+            state.lap_distance = Some(lap.lap_distance);
+            let previous = state.total_distance.replace(lap.total_distance);
+            let step = match previous {
+                Some(previous) => lap.total_distance - previous,
+                None => continue,
+            };
+
+            // A car that drives forward, stops or is reset to the track starts over.
+            if !(-MAX_STEP..0.0).contains(&step) {
+                state.backwards = 0.0;
+                state.wrong_way = false;
+                continue;
+            }
+
+            state.backwards -= step;
+            if state.backwards < WRONG_WAY_DISTANCE || state.wrong_way {
+                continue;
+            }
+
+            state.wrong_way = true;
+            if let Some((timestamp, position)) = state.position {
+                detections.push(Detection {
+                    index,
+                    kind: ControlLossKind::WrongWay,
+                    timestamp,
+                    position,
+                    lap_distance: state.lap_distance,
+                });
+            }
+        }
+
+        detections
+    }
+}
+
+// Wrap an angle in radians to the range -π to π.
+fn wrap(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::lap_data;
+    use crate::frame::motion::{CarMotion, PlayerMotion};
+    use crate::race::control::{ControlDetector, ControlLossKind, Detection};
+
+    // A car driving 40 m/s along the z-axis, pointing `angle` radians away from the direction of
+    // travel.
+    fn motion(z: f32, angle: f32) -> CarMotion {
+        CarMotion {
+            world_position_x: 0.0,
+            world_position_y: 0.0,
+            world_position_z: z,
+            world_velocity_x: 0.0,
+            world_velocity_y: 0.0,
+            world_velocity_z: 40.0,
+            world_forward_direction_x: (angle.sin() * 32767.0) as i16 as u16,
+            world_forward_direction_y: 0,
+            world_forward_direction_z: (angle.cos() * 32767.0) as i16 as u16,
+            world_right_direction_x: 0,
+            world_right_direction_y: 0,
+            world_right_direction_z: 0,
+            g_force_lateral: 0.0,
+            g_force_longitudinal: 0.0,
+            g_force_vertical: 1.0,
+            yaw: angle,
+            pitch: 0.0,
+            roll: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_slide_and_spin() {
+        let mut detector = ControlDetector::default();
+        let player = PlayerMotion::default();
+        // The first car slides up to 0.5 radians and recovers, the second car spins.
+        let angles = [
+            (0.0, 0.0),
+            (0.2, 0.3),
+            (0.35, 0.6),
+            (0.5, 1.0),
+            (0.2, 2.0),
+            (0.0, 3.0),
+        ];
+
+        let mut detections = vec![];
+        for (i, (first, second)) in angles.iter().enumerate() {
+            let z = i as f32 * 4.0;
+            let motions = [motion(z, *first), motion(z, *second)];
+            detections.extend(detector.update_motion(i as f32 * 0.1, &motions, 255, &player));
+        }
+
+        assert_eq!(detections.len(), 2);
+        assert_eq!(detections[0].index, 1);
+        assert_eq!(detections[0].kind, ControlLossKind::Spin);
+        assert_eq!(detections[1].index, 0);
+        assert!((detections[1].timestamp - 0.3).abs() < 0.001);
+        match detections[1].kind {
+            ControlLossKind::Slide { slip_angle } => assert!((slip_angle - 0.5).abs() < 0.01),
+            kind => panic!("Expected a slide, got {:?}", kind),
+        }
+    }
+
+    // Feed a Lap Data frame for every pair of lap distance and total distance of a single car.
+    // Returns the detections.
+    fn drive(detector: &mut ControlDetector, distances: &[(f32, f32)]) -> Vec<Detection> {
+        let mut detections = vec![];
+        for (lap_distance, total_distance) in distances.iter() {
+            let mut lap = lap_data(1);
+            lap.lap_distance = *lap_distance;
+            lap.total_distance = *total_distance;
+            detections.extend(detector.update_lap_data(&[lap]));
+        }
+        detections
+    }
+
+    // Distances of a car driving from `from` to `to` metres of total distance in steps of 10
+    // metres, on a lap of 1000 metres. The car is at `from` already.
+    fn steps(from: f32, to: f32) -> Vec<(f32, f32)> {
+        let count = ((to - from).abs() / 10.0) as usize;
+        (1..=count)
+            .map(|i| {
+                let total_distance = from + (to - from).signum() * i as f32 * 10.0;
+                (total_distance.rem_euclid(1000.0), total_distance)
+            })
+            .collect()
+    }
+
+    // A detector that knows the position of the car.
+    fn moving_car() -> ControlDetector {
+        let mut detector = ControlDetector::default();
+        detector.update_motion(0.0, &[motion(0.0, 0.0)], 255, &PlayerMotion::default());
+        detector
+    }
+
+    #[test]
+    fn test_wrong_way() {
+        let mut detector = moving_car();
+        assert!(drive(&mut detector, &steps(200.0, 160.0)).is_empty());
+
+        // Reversing past the wrong way distance is detected once.
+        let detections = drive(&mut detector, &steps(160.0, 100.0));
+        assert_eq!(detections.len(), 1);
+        assert_eq!(detections[0].kind, ControlLossKind::WrongWay);
+        assert_eq!(detections[0].lap_distance, Some(140.0));
+        assert!(drive(&mut detector, &steps(100.0, 0.0)).is_empty());
+
+        // Driving forward starts over.
+        let mut detector = moving_car();
+        drive(&mut detector, &steps(200.0, 160.0));
+        drive(&mut detector, &steps(160.0, 170.0));
+        assert!(drive(&mut detector, &steps(170.0, 130.0)).is_empty());
+        assert_eq!(drive(&mut detector, &steps(130.0, 110.0)).len(), 1);
+    }
+
+    #[test]
+    fn test_wrong_way_after_stop() {
+        let mut detector = moving_car();
+        drive(&mut detector, &steps(200.0, 160.0));
+
+        // A stopped car starts over, a short reverse afterwards isn't driving the wrong way.
+        drive(&mut detector, &[(160.0, 160.0), (160.0, 160.0)]);
+        assert!(drive(&mut detector, &steps(160.0, 140.0)).is_empty());
+    }
+
+    #[test]
+    fn test_wrong_way_across_start_line() {
+        let mut detector = moving_car();
+        // Driving forward across the start line isn't driving the wrong way.
+        assert!(drive(&mut detector, &steps(950.0, 1020.0)).is_empty());
+
+        // Reversing across the start line is.
+        let detections = drive(&mut detector, &steps(1020.0, 960.0));
+        assert_eq!(detections.len(), 1);
+        assert_eq!(detections[0].lap_distance, Some(970.0));
+    }
+}
@@ -0,0 +1,407 @@
+use crate::frame::{
+    event::{InfringementType, PenaltyType},
+    lap_data::{LapData, PitStatus, ResultStatus},
+};
+use crate::race::{Car, SpatialLocation, Status, Timestamp};
+
+/// `RaceEvent` is an event derived from the raw frames. Contrary to `EventDetails`, the cars
+/// involved are resolved to a `Car`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RaceEvent {
+    /// `car` took over the position of `overtaken`.
+    Overtake { car: Car, overtaken: Car },
+    /// Position of `car` changed.
+    PositionChange { car: Car, from: u8, to: u8 },
+    /// `car` entered the pit lane.
+    PitEntry { car: Car, lap: u8 },
+    /// `car` left the pit lane.
+    PitExit { car: Car, lap: u8 },
+    /// `car` completed a lap.
+    LapCompleted {
+        car: Car,
+        lap: u8,
+        /// Lap time in seconds.
+        lap_time: f32,
+        sector_1_time_in_ms: u16,
+        sector_2_time_in_ms: u16,
+        /// Whether the lap was invalidated, for example by cutting a corner.
+        invalid: bool,
+    },
+    /// `car` improved its best lap of the session.
+    PersonalBestLap {
+        car: Car,
+        /// Lap time in seconds.
+        lap_time: f32,
+    },
+    /// `car` set the fastest lap of the session.
+    OverallBestLap {
+        car: Car,
+        /// Lap time in seconds.
+        lap_time: f32,
+    },
+    /// `car` retired from the session.
+    Retirement { car: Car },
+    /// `car` received a penalty.
+    Penalty {
+        car: Car,
+        /// The other car involved in the foul, if any.
+        other_car: Option<Car>,
+        penalty_type: PenaltyType,
+        infringement_type: InfringementType,
+        time: u8,
+        lap_number: u8,
+        places_gained: u8,
+    },
+    /// `car` probably touched `other_car`. See `Impact`.
+    Contact {
+        car: Car,
+        other_car: Car,
+        location: SpatialLocation,
+        /// Peak deceleration in g.
+        severity: f32,
+    },
+    /// `car` probably hit a wall. See `Impact`.
+    WallHit {
+        car: Car,
+        location: SpatialLocation,
+        /// Peak deceleration in g.
+        severity: f32,
+    },
+    /// `car` spun. See `ControlLoss`.
+    Spin {
+        car: Car,
+        location: SpatialLocation,
+        /// Distance in metres along the lap.
+        lap_distance: Option<f32>,
+    },
+    /// `car` slid, but the driver caught it. See `ControlLoss`.
+    Slide {
+        car: Car,
+        location: SpatialLocation,
+        /// Distance in metres along the lap.
+        lap_distance: Option<f32>,
+        /// Peak slip angle in radians.
+        slip_angle: f32,
+    },
+    /// `car` drives the wrong way. See `ControlLoss`.
+    WrongWay {
+        car: Car,
+        location: SpatialLocation,
+        /// Distance in metres along the lap.
+        lap_distance: Option<f32>,
+    },
+    /// The session time jumped back from `from` to `to`, for example because the player used a
+    /// flashback. Everything that happened after `to` has been undone.
+    Rewind { from: Timestamp, to: Timestamp },
+    /// `Status` of the race changed.
+    StatusChange { from: Status, to: Status },
+}
+
+/// Compare two consecutive Lap Data frames and derive the `RaceEvent`s that happened in between.
+/// `resolve` maps a vehicle index to a `Car`. Vehicles that can't be resolved are ignored.
+pub(crate) fn lap_data_events<F>(
+    previous: &[LapData],
+    current: &[LapData],
+    overall_best_lap_time: &mut Option<f32>,
+    resolve: F,
+) -> Vec<RaceEvent>
+where
+    F: Fn(usize) -> Option<Car>,
+{
+    let mut events = vec![];
+
+    for (index, (before, after)) in previous.iter().zip(current.iter()).enumerate() {
+        let car = match resolve(index) {
+            Some(car) => car,
+            None => continue,
+        };
+
+        if after.current_lap_num > before.current_lap_num && before.current_lap_num > 0 {
+            events.push(RaceEvent::LapCompleted {
+                car: car.clone(),
+                lap: before.current_lap_num,
+                lap_time: after.last_lap_time,
+                sector_1_time_in_ms: before.sector_1_time_in_ms,
+                sector_2_time_in_ms: before.sector_2_time_in_ms,
+                invalid: before.current_lap_invalid != 0,
+            });
+        }
+
+        if after.best_lap_time > 0.0
+            && (before.best_lap_time <= 0.0 || after.best_lap_time < before.best_lap_time)
+        {
+            events.push(RaceEvent::PersonalBestLap {
+                car: car.clone(),
+                lap_time: after.best_lap_time,
+            });
+
+            let is_overall_best = match overall_best_lap_time {
+                Some(best) => after.best_lap_time < *best,
+                None => true,
+            };
+            if is_overall_best {
+                *overall_best_lap_time = Some(after.best_lap_time);
+                events.push(RaceEvent::OverallBestLap {
+                    car: car.clone(),
+                    lap_time: after.best_lap_time,
+                });
+            }
+        }
+
+        match (before.pit_status, after.pit_status) {
+            (PitStatus::None, PitStatus::Pitting) | (PitStatus::None, PitStatus::InPitArea) => {
+                events.push(RaceEvent::PitEntry {
+                    car: car.clone(),
+                    lap: after.current_lap_num,
+                })
+            }
+            (PitStatus::Pitting, PitStatus::None) | (PitStatus::InPitArea, PitStatus::None) => {
+                events.push(RaceEvent::PitExit {
+                    car: car.clone(),
+                    lap: after.current_lap_num,
+                })
+            }
+            _ => {}
+        }
+
+        if before.car_position != after.car_position
+            && before.car_position > 0
+            && after.car_position > 0
+        {
+            events.push(RaceEvent::PositionChange {
+                car,
+                from: before.car_position,
+                to: after.car_position,
+            });
+        }
+    }
+
+    events.append(&mut overtakes(previous, current, resolve));
+    events
+}
+
+// A car overtakes another car when it was behind that car in the previous frame and is in front
+// of it in the current frame. Swaps caused by a car pitting or dropping out don't count.
+fn overtakes<F>(previous: &[LapData], current: &[LapData], resolve: F) -> Vec<RaceEvent>
+where
+    F: Fn(usize) -> Option<Car>,
+{
+    let racing = |lap_data: &LapData| {
+        lap_data.result_status == ResultStatus::Active
+            && lap_data.pit_status == PitStatus::None
+            && lap_data.car_position > 0
+    };
+
+    let mut events = vec![];
+    let cars = previous.len().min(current.len());
+    for i in 0..cars {
+        for j in 0..cars {
+            if i == j
+                || !racing(&previous[i])
+                || !racing(&previous[j])
+                || !racing(&current[i])
+                || !racing(&current[j])
+            {
+                continue;
+            }
+
+            if previous[i].car_position > previous[j].car_position
+                && current[i].car_position < current[j].car_position
+            {
+                if let (Some(car), Some(overtaken)) = (resolve(i), resolve(j)) {
+                    events.push(RaceEvent::Overtake { car, overtaken });
+                }
+            }
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::{lap_data, participant};
+    use crate::frame::{
+        lap_data::PitStatus,
+        participants::{Driver, Team},
+    };
+    use crate::race::{
+        event::{lap_data_events, RaceEvent},
+        Car,
+    };
+
+    fn car(index: usize) -> Option<Car> {
+        let participant = match index {
+            0 => participant(Driver::LewisHemilton, Team::Mercedes, 44),
+            _ => participant(Driver::MaxVerstappen, Team::RedBulRacing, 33),
+        };
+        Some(Car {
+            index: index as u8,
+            participant,
+        })
+    }
+
+    #[test]
+    fn test_overtake() {
+        let previous = [lap_data(1), lap_data(2)];
+        let current = [lap_data(2), lap_data(1)];
+
+        let events = lap_data_events(&previous, &current, &mut None, car);
+        assert!(events.contains(&RaceEvent::Overtake {
+            car: car(1).unwrap(),
+            overtaken: car(0).unwrap(),
+        }));
+        assert!(events.contains(&RaceEvent::PositionChange {
+            car: car(0).unwrap(),
+            from: 1,
+            to: 2,
+        }));
+
+        let mut pitting = lap_data(2);
+        pitting.pit_status = PitStatus::Pitting;
+        let current = [pitting, lap_data(1)];
+
+        let events = lap_data_events(&previous, &current, &mut None, car);
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, RaceEvent::Overtake { .. })));
+    }
+
+    #[test]
+    fn test_lap_completed() {
+        let previous = [lap_data(1), lap_data(2)];
+        let mut next_lap = lap_data(1);
+        next_lap.current_lap_num = 2;
+        next_lap.last_lap_time = 91.5;
+        let current = [next_lap, lap_data(2)];
+
+        let events = lap_data_events(&previous, &current, &mut None, car);
+        assert!(events.contains(&RaceEvent::LapCompleted {
+            car: car(0).unwrap(),
+            lap: 1,
+            lap_time: 91.5,
+            sector_1_time_in_ms: 0,
+            sector_2_time_in_ms: 0,
+            invalid: false,
+        }));
+        assert_eq!(events.len(), 1);
+    }
+
+    #[test]
+    fn test_best_laps() {
+        let mut best = Some(90.0);
+        let mut previous = [lap_data(1), lap_data(2)];
+        previous[0].best_lap_time = 92.0;
+        let mut current = previous;
+        current[0].best_lap_time = 91.0;
+        current[1].best_lap_time = 89.5;
+
+        let events = lap_data_events(&previous, &current, &mut best, car);
+        assert_eq!(
+            events,
+            vec![
+                RaceEvent::PersonalBestLap {
+                    car: car(0).unwrap(),
+                    lap_time: 91.0,
+                },
+                RaceEvent::PersonalBestLap {
+                    car: car(1).unwrap(),
+                    lap_time: 89.5,
+                },
+                RaceEvent::OverallBestLap {
+                    car: car(1).unwrap(),
+                    lap_time: 89.5,
+                },
+            ]
+        );
+        assert_eq!(best, Some(89.5));
+
+        // A lap time that didn't change isn't a new best lap.
+        let events = lap_data_events(&current, &current, &mut best, car);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_pit_entry_and_exit() {
+        let previous = [lap_data(1), lap_data(2)];
+        let mut pitting = lap_data(1);
+        pitting.pit_status = PitStatus::Pitting;
+        let current = [pitting, lap_data(2)];
+
+        let events = lap_data_events(&previous, &current, &mut None, car);
+        assert_eq!(
+            events,
+            vec![RaceEvent::PitEntry {
+                car: car(0).unwrap(),
+                lap: 1,
+            }]
+        );
+
+        let mut in_pit_area = pitting;
+        in_pit_area.pit_status = PitStatus::InPitArea;
+        let events = lap_data_events(&current, &[in_pit_area, lap_data(2)], &mut None, car);
+        assert!(events.is_empty());
+
+        let events = lap_data_events(&[in_pit_area, lap_data(2)], &previous, &mut None, car);
+        assert_eq!(
+            events,
+            vec![RaceEvent::PitExit {
+                car: car(0).unwrap(),
+                lap: 1,
+            }]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use crate::frame::event::{InfringementType, PenaltyType};
+        use crate::race::{CarId, SpatialLocation, Status};
+
+        let events = vec![
+            RaceEvent::Overtake {
+                car: car(0).unwrap(),
+                overtaken: car(1).unwrap(),
+            },
+            RaceEvent::LapCompleted {
+                car: car(0).unwrap(),
+                lap: 3,
+                lap_time: 91.234,
+                sector_1_time_in_ms: 30_100,
+                sector_2_time_in_ms: 31_200,
+                invalid: false,
+            },
+            RaceEvent::Penalty {
+                car: car(0).unwrap(),
+                other_car: None,
+                penalty_type: PenaltyType::TimePenalty,
+                infringement_type: InfringementType::CornerCuttingGainedTime,
+                time: 5,
+                lap_number: 3,
+                places_gained: 0,
+            },
+            RaceEvent::Spin {
+                car: car(0).unwrap(),
+                location: SpatialLocation {
+                    car: CarId::from(1),
+                    timestamp: 12.5,
+                    coords: (1.0, -2.0, 3.5),
+                },
+                lap_distance: None,
+            },
+            RaceEvent::Rewind {
+                from: 20.0,
+                to: 10.0,
+            },
+            RaceEvent::StatusChange {
+                from: Status::FormationLap,
+                to: Status::Racing,
+            },
+        ];
+        for event in events {
+            let json = serde_json::to_string(&event).unwrap();
+            assert_eq!(serde_json::from_str::<RaceEvent>(&json).unwrap(), event);
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use crate::frame::car_status::{CarStatus, FuelMix};
+
+/// Number of laps the rolling average consumption is calculated over by default.
+pub const DEFAULT_WINDOW: usize = 3;
+
+/// Fuel used by a car during a single lap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LapFuel {
+    pub lap: u8,
+    /// Fuel used in kilograms.
+    pub used: f32,
+}
+
+/// Projection of the fuel of a car at the finish. See `Race::fuel_projection()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FuelProjection {
+    /// Laps the car still has to drive, including the rest of the current lap.
+    pub laps_remaining: f32,
+    /// Fuel in kilograms that's left in the tank at the finish.
+    pub fuel_at_finish: f32,
+    /// Surplus of fuel at the finish, expressed in laps. See `FuelModel::fuel_delta()`.
+    pub delta: f32,
+}
+
+/// `FuelModel` estimates the fuel consumption of a car. Every time the car starts a new lap, the
+/// fuel used during the previous lap is recorded. The projections are based on the average
+/// consumption over the last `window` laps.
+///
+/// The game reports its own estimate in `CarStatus::fuel_remaining_laps`, but that value is
+/// noisy and it's not clear how it's calculated.
+#[derive(Debug, Clone)]
+pub struct FuelModel {
+    /// Fuel used per completed lap.
+    pub laps: Vec<LapFuel>,
+    window: usize,
+    current_lap: u8,
+    // Whether the fuel at the start of the current lap is unknown, for example because the car
+    // was sent back to the middle of the lap by a flashback.
+    incomplete_lap: bool,
+    fuel_at_lap_start: Option<f32>,
+    fuel_in_tank: Option<f32>,
+    fuel_mix: Option<FuelMix>,
+}
+
+impl FuelModel {
+    pub fn new(window: usize) -> Self {
+        FuelModel {
+            laps: vec![],
+            window: window.max(1),
+            current_lap: 0,
+            incomplete_lap: false,
+            fuel_at_lap_start: None,
+            fuel_in_tank: None,
+            fuel_mix: None,
+        }
+    }
+
+    /// Fuel in the tank in kilograms.
+    pub fn fuel_in_tank(&self) -> Option<f32> {
+        self.fuel_in_tank
+    }
+
+    /// Average fuel used per lap in kilograms over the last `window` laps.
+    pub fn average_consumption(&self) -> Option<f32> {
+        let laps: Vec<f32> = self
+            .laps
+            .iter()
+            .rev()
+            // A negative value means fuel was added, that lap doesn't tell anything about the
+            // consumption.
+            .filter(|lap| lap.used > 0.0)
+            .take(self.window)
+            .map(|lap| lap.used)
+            .collect();
+
+        if laps.is_empty() {
+            return None;
+        }
+
+        Some(laps.iter().sum::<f32>() / laps.len() as f32)
+    }
+
+    /// Fuel in kilograms that's left in the tank after `laps_remaining` laps at the average
+    /// consumption.
+    pub fn projected_fuel_at_finish(&self, laps_remaining: f32) -> Option<f32> {
+        let fuel_in_tank = self.fuel_in_tank?;
+        let consumption = self.average_consumption()?;
+
+        Some(fuel_in_tank - consumption * laps_remaining)
+    }
+
+    /// Surplus of fuel at the finish, expressed in laps. A negative value means the car runs out
+    /// of fuel before the finish at the average consumption.
+    pub fn fuel_delta(&self, laps_remaining: f32) -> Option<f32> {
+        let projected = self.projected_fuel_at_finish(laps_remaining)?;
+        let consumption = self.average_consumption()?;
+
+        Some(projected / consumption)
+    }
+
+    /// Project the fuel at the finish after `laps_remaining` laps at the average consumption.
+    pub fn projection(&self, laps_remaining: f32) -> Option<FuelProjection> {
+        Some(FuelProjection {
+            laps_remaining,
+            fuel_at_finish: self.projected_fuel_at_finish(laps_remaining)?,
+            delta: self.fuel_delta(laps_remaining)?,
+        })
+    }
+
+    /// Recommend a `FuelMix` so the car finishes with a surplus of at least `target_delta` laps.
+    /// A leaner mix is recommended if the fuel delta drops below the target. A richer mix is
+    /// recommended if the surplus exceeds the target by more than a lap.
+    pub fn recommended_fuel_mix(&self, laps_remaining: f32, target_delta: f32) -> Option<FuelMix> {
+        let delta = self.fuel_delta(laps_remaining)?;
+        let fuel_mix = self.fuel_mix?;
+
+        if delta < target_delta {
+            return Some(leaner(fuel_mix));
+        }
+
+        if delta > target_delta + 1.0 {
+            return Some(richer(fuel_mix));
+        }
+
+        Some(fuel_mix)
+    }
+
+    pub(crate) fn update_lap(&mut self, lap: u8) {
+        if lap == self.current_lap {
+            return;
+        }
+
+        // The lap number goes down after a flashback. The laps from then on have been undone.
+        // The flashback can go back to any point of the lap, so the fuel used during the lap
+        // that is driven again isn't known.
+        if lap < self.current_lap {
+            self.laps.retain(|lap_fuel| lap_fuel.lap < lap);
+            self.current_lap = lap;
+            self.incomplete_lap = true;
+            return;
+        }
+
+        if let (Some(start), Some(now)) = (self.fuel_at_lap_start, self.fuel_in_tank) {
+            if self.current_lap > 0 && !self.incomplete_lap {
+                self.laps.push(LapFuel {
+                    lap: self.current_lap,
+                    used: start - now,
+                });
+            }
+        }
+
+        self.current_lap = lap;
+        self.incomplete_lap = false;
+        self.fuel_at_lap_start = self.fuel_in_tank;
+    }
+
+    pub(crate) fn update_car_status(&mut self, car_status: &CarStatus) {
+        self.fuel_in_tank = Some(car_status.fuel_in_tank);
+        self.fuel_mix = Some(car_status.fuel_mix);
+        if self.fuel_at_lap_start.is_none() {
+            self.fuel_at_lap_start = self.fuel_in_tank;
+        }
+    }
+}
+
+impl Default for FuelModel {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW)
+    }
+}
+
+fn leaner(fuel_mix: FuelMix) -> FuelMix {
+    match fuel_mix {
+        FuelMix::Max => FuelMix::Rich,
+        FuelMix::Rich => FuelMix::Standard,
+        FuelMix::Standard | FuelMix::Lean => FuelMix::Lean,
+    }
+}
+
+fn richer(fuel_mix: FuelMix) -> FuelMix {
+    match fuel_mix {
+        FuelMix::Lean => FuelMix::Standard,
+        FuelMix::Standard => FuelMix::Rich,
+        FuelMix::Rich | FuelMix::Max => FuelMix::Max,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::car_status;
+    use crate::frame::car_status::FuelMix;
+    use crate::race::fuel::{FuelModel, FuelProjection, LapFuel};
+
+    fn update_fuel(model: &mut FuelModel, fuel_in_tank: f32) {
+        let mut status = car_status();
+        status.fuel_in_tank = fuel_in_tank;
+        model.update_car_status(&status);
+    }
+
+    // Drive lap 1 and a lap for every value of `laps`, using that much fuel.
+    fn model(fuel_in_tank: f32, laps: &[f32]) -> FuelModel {
+        let mut model = FuelModel::new(2);
+        let mut fuel_in_tank = fuel_in_tank + laps.iter().sum::<f32>();
+        update_fuel(&mut model, fuel_in_tank);
+        model.update_lap(1);
+
+        for (i, used) in laps.iter().enumerate() {
+            fuel_in_tank -= used;
+            update_fuel(&mut model, fuel_in_tank);
+            model.update_lap(i as u8 + 2);
+        }
+        model
+    }
+
+    #[test]
+    fn test_projection() {
+        let model = model(20.0, &[3.0, 1.5, 2.5]);
+        assert_eq!(model.fuel_in_tank(), Some(20.0));
+        assert_eq!(model.average_consumption(), Some(2.0));
+        assert_eq!(model.projected_fuel_at_finish(8.0), Some(4.0));
+        assert_eq!(model.fuel_delta(8.0), Some(2.0));
+
+        assert_eq!(model.recommended_fuel_mix(8.0, 0.5), Some(FuelMix::Rich));
+        assert_eq!(
+            model.recommended_fuel_mix(8.0, 1.5),
+            Some(FuelMix::Standard)
+        );
+        assert_eq!(model.recommended_fuel_mix(11.0, 0.0), Some(FuelMix::Lean));
+    }
+
+    #[test]
+    fn test_update() {
+        let mut model = FuelModel::default();
+        assert_eq!(model.average_consumption(), None);
+
+        for (lap, fuel) in [(1, 30.0), (2, 28.0), (3, 26.5)].iter() {
+            update_fuel(&mut model, *fuel);
+            model.update_lap(*lap);
+        }
+
+        assert_eq!(model.laps.len(), 2);
+        assert_eq!(model.average_consumption(), Some(1.75));
+
+        // Refuelling doesn't count as consumption.
+        update_fuel(&mut model, 40.0);
+        model.update_lap(4);
+        assert_eq!(model.laps.len(), 3);
+        assert_eq!(model.average_consumption(), Some(1.75));
+    }
+
+    #[test]
+    fn test_lap_goes_down() {
+        let mut model = model(20.0, &[3.0, 2.0, 2.0]);
+        assert_eq!(model.laps.len(), 3);
+
+        // A flashback to lap 2 undoes laps 2 and 3.
+        update_fuel(&mut model, 24.0);
+        model.update_lap(2);
+        assert_eq!(model.laps, vec![LapFuel { lap: 1, used: 3.0 }]);
+
+        // Lap 2 is driven again, but it's not known where the flashback went to.
+        update_fuel(&mut model, 22.5);
+        model.update_lap(3);
+        assert_eq!(model.laps, vec![LapFuel { lap: 1, used: 3.0 }]);
+
+        update_fuel(&mut model, 20.5);
+        model.update_lap(4);
+        assert_eq!(
+            model.laps,
+            vec![LapFuel { lap: 1, used: 3.0 }, LapFuel { lap: 3, used: 2.0 }]
+        );
+    }
+
+    #[test]
+    fn test_flashback_mid_lap() {
+        let mut model = model(20.0, &[3.0, 2.0, 2.0]);
+
+        // A flashback to the middle of lap 3, after half of its fuel was used.
+        update_fuel(&mut model, 21.0);
+        model.update_lap(3);
+        assert_eq!(model.laps.len(), 2);
+
+        // Only the second half of lap 3 is driven again, so it's not recorded.
+        update_fuel(&mut model, 20.0);
+        model.update_lap(4);
+        assert_eq!(model.laps.len(), 2);
+
+        update_fuel(&mut model, 18.0);
+        model.update_lap(5);
+        assert_eq!(model.laps.last(), Some(&LapFuel { lap: 4, used: 2.0 }));
+        assert_eq!(model.average_consumption(), Some(2.0));
+        assert_eq!(
+            model.projection(3.0),
+            Some(FuelProjection {
+                laps_remaining: 3.0,
+                fuel_at_finish: 12.0,
+                delta: 6.0,
+            })
+        );
+    }
+}
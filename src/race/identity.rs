@@ -0,0 +1,171 @@
+use std::fmt;
+
+use crate::frame::participants::{Driver, Participant, Team};
+
+/// `CarId` identifies a car and its driver.
+///
+/// In online sessions all human drivers share the same `Driver`, so `Driver` can't tell them
+/// apart. `CarId` is derived from the race number, team and name of a participant instead. It's
+/// the same on every machine and every run, so it can be stored.
+///
+/// The `CarId` of an AI driver doesn't depend on the vehicle index, so it doesn't change between
+/// sessions when the grid order changes. Human drivers can share all of these details, for
+/// example when their names are hidden as "Player", so their vehicle index is included as
+/// well. Human drivers keep their vehicle index between the sessions of a lobby.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CarId(u64);
+
+impl CarId {
+    /// Derive the `CarId` of `participant`, the car with vehicle index `index`.
+    pub fn new(index: u8, participant: &Participant) -> Self {
+        let mut hash = Fnv::new();
+        // The id of the team in the API is hashed, so the id doesn't depend on the order of the
+        // variants of `Team`.
+        hash.write(&[participant.race_number, u8::from(participant.team)]);
+        hash.write(participant.name.as_bytes());
+        if participant.ai_controlled == 0 {
+            hash.write(&[index]);
+        }
+
+        CarId(hash.finish())
+    }
+}
+
+impl From<u64> for CarId {
+    fn from(value: u64) -> Self {
+        CarId(value)
+    }
+}
+
+impl From<CarId> for u64 {
+    fn from(id: CarId) -> Self {
+        id.0
+    }
+}
+
+impl fmt::Display for CarId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// `DriverIdentity` is the `CarId` of a car together with the details it's derived from.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DriverIdentity {
+    pub id: CarId,
+    /// Index of the car in the frames of the F1 2020 API.
+    pub index: u8,
+    pub race_number: u8,
+    pub team: Team,
+    pub name: String,
+    /// The driver as reported by the game. Human drivers in online sessions all share the same
+    /// `Driver`.
+    pub driver: Driver,
+}
+
+impl DriverIdentity {
+    pub fn new(index: u8, participant: &Participant) -> Self {
+        DriverIdentity {
+            id: CarId::new(index, participant),
+            index,
+            race_number: participant.race_number,
+            team: participant.team,
+            name: participant.name.clone(),
+            driver: participant.driver_id,
+        }
+    }
+}
+
+// The 64 bit FNV-1a hash. Unlike the hasher of the standard library its output is specified,
+// so it doesn't change between Rust versions.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::participants_frame;
+    use crate::frame::{
+        participants::{Driver, Participant, Team},
+        Data,
+    };
+    use crate::race::{identity::CarId, Race};
+
+    fn participant(name: &str) -> Participant {
+        Participant {
+            ai_controlled: 0,
+            driver_id: Driver::LewisHemilton,
+            team: Team::Williams,
+            race_number: 6,
+            nationality: 0,
+            name: name.to_string(),
+            your_telemetry: 1,
+        }
+    }
+
+    #[test]
+    fn test_car_id() {
+        let id = CarId::new(0, &participant("Player"));
+        assert_eq!(id, CarId::new(0, &participant("Player")));
+        assert_ne!(id, CarId::new(0, &participant("Other player")));
+        assert_eq!(CarId::from(u64::from(id)), id);
+
+        let mut other_team = participant("Player");
+        other_team.team = Team::Ferrari;
+        assert_ne!(id, CarId::new(0, &other_team));
+
+        // Human drivers with hidden names in the same team with the same race number are told
+        // apart by their vehicle index.
+        assert_ne!(id, CarId::new(1, &participant("Player")));
+
+        // AI drivers are identified by their details only.
+        let mut ai = participant("Bot");
+        ai.ai_controlled = 1;
+        assert_eq!(CarId::new(0, &ai), CarId::new(1, &ai));
+    }
+
+    #[test]
+    fn test_car_id_after_grid_change() {
+        let mut race = Race::new();
+        race.feed_frame(participants_frame(0.0));
+        let bottas = race.car(1).unwrap().id();
+
+        // In the next session Valtteri Bottas starts from the first slot.
+        let mut frame = participants_frame(1.0);
+        if let Data::Participants(body) = &mut frame.body {
+            body.participants.reverse();
+        }
+        race.feed_frame(frame);
+        assert_eq!(race.car(0).unwrap().id(), bottas);
+        assert_ne!(race.car(1).unwrap().id(), bottas);
+    }
+
+    #[test]
+    fn test_car_id_of_players() {
+        // 2 human drivers with hidden names in the same team with the same race number.
+        let mut frame = participants_frame(0.0);
+        if let Data::Participants(body) = &mut frame.body {
+            body.participants = vec![participant("Player"), participant("Player")];
+        }
+        let mut race = Race::new();
+        race.feed_frame(frame);
+        assert_ne!(race.car(0).unwrap().id(), race.car(1).unwrap().id());
+    }
+}
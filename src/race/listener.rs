@@ -0,0 +1,132 @@
+use crate::race::{event::RaceEvent, Car, Status};
+
+/// `Listener` is notified of the changes of a `Race`. Register a `Listener` with
+/// `Race::subscribe()`.
+///
+/// All methods have an empty default implementation, so implementors only have to implement the
+/// methods they're interested in. Listeners must be `Send`, so a `Race` can be moved to another
+/// thread.
+pub trait Listener: Send {
+    /// Called for every `RaceEvent` derived by the `Race`.
+    fn on_event(&mut self, _event: &RaceEvent) {}
+
+    /// Called when a car completed a lap. `lap_time` is in seconds.
+    fn on_lap_completed(&mut self, _car: &Car, _lap: u8, _lap_time: f32) {}
+
+    /// Called when the `Status` of the `Race` changed.
+    fn on_status_change(&mut self, _from: Status, _to: Status) {}
+}
+
+// Notify `listener` of `event`, calling the specialized method if there is one.
+pub(crate) fn notify(listener: &mut dyn Listener, event: &RaceEvent) {
+    listener.on_event(event);
+    match event {
+        RaceEvent::LapCompleted {
+            car, lap, lap_time, ..
+        } => listener.on_lap_completed(car, *lap, *lap_time),
+        RaceEvent::StatusChange { from, to } => listener.on_status_change(*from, *to),
+        _ => {}
+    }
+}
+
+pub(crate) struct OnEvent<F>(pub(crate) F);
+
+impl<F: FnMut(&RaceEvent) + Send> Listener for OnEvent<F> {
+    fn on_event(&mut self, event: &RaceEvent) {
+        (self.0)(event)
+    }
+}
+
+pub(crate) struct OnLapCompleted<F>(pub(crate) F);
+
+impl<F: FnMut(&Car, u8, f32) + Send> Listener for OnLapCompleted<F> {
+    fn on_lap_completed(&mut self, car: &Car, lap: u8, lap_time: f32) {
+        (self.0)(car, lap, lap_time)
+    }
+}
+
+pub(crate) struct OnStatusChange<F>(pub(crate) F);
+
+impl<F: FnMut(Status, Status) + Send> Listener for OnStatusChange<F> {
+    fn on_status_change(&mut self, from: Status, to: Status) {
+        (self.0)(from, to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::fixtures::{frame, lap_data, lap_data_frame, participants_frame};
+    use crate::frame::{
+        event::{EventBody, EventDetails},
+        Data,
+    };
+    use crate::race::{event::RaceEvent, listener::Listener, Car, Race, Status};
+
+    // Records the laps it's notified of.
+    struct Laps(Arc<Mutex<Vec<(u8, u8)>>>);
+
+    impl Listener for Laps {
+        fn on_lap_completed(&mut self, car: &Car, lap: u8, _lap_time: f32) {
+            self.0.lock().unwrap().push((car.index, lap));
+        }
+    }
+
+    #[test]
+    fn test_listeners() {
+        let mut race = Race::new();
+
+        let subscribed = Arc::new(Mutex::new(vec![]));
+        race.subscribe(Box::new(Laps(subscribed.clone())));
+
+        let events = Arc::new(Mutex::new(vec![]));
+        let on_event = events.clone();
+        race.on_event(move |event| on_event.lock().unwrap().push(event.clone()));
+
+        let laps = Arc::new(Mutex::new(vec![]));
+        let on_lap_completed = laps.clone();
+        race.on_lap_completed(move |car, lap, lap_time| {
+            on_lap_completed
+                .lock()
+                .unwrap()
+                .push((car.index, lap, lap_time))
+        });
+
+        let statuses = Arc::new(Mutex::new(vec![]));
+        let on_status_change = statuses.clone();
+        race.on_status_change(move |from, to| on_status_change.lock().unwrap().push((from, to)));
+
+        race.feed_frame(participants_frame(0.0));
+        race.feed_frame(frame(
+            0.1,
+            Data::Event(EventBody {
+                code: "SSTA".to_string(),
+                details: EventDetails::SessionStarted,
+            }),
+        ));
+        race.feed_frame(lap_data_frame(1.0, vec![lap_data(1), lap_data(2)]));
+
+        let mut next_lap = lap_data(2);
+        next_lap.current_lap_num = 2;
+        next_lap.last_lap_time = 93.25;
+        race.feed_frame(lap_data_frame(2.0, vec![lap_data(1), next_lap]));
+
+        assert_eq!(*subscribed.lock().unwrap(), vec![(1, 1)]);
+        assert_eq!(*laps.lock().unwrap(), vec![(1, 1, 93.25)]);
+        assert_eq!(
+            *statuses.lock().unwrap(),
+            vec![(Status::Unknown, Status::Unfolding)]
+        );
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], RaceEvent::StatusChange { .. }));
+        assert!(matches!(events[1], RaceEvent::LapCompleted { lap: 1, .. }));
+
+        // Listeners are `Send`, so the race can be moved to another thread.
+        std::thread::spawn(move || race.feed_frame(participants_frame(3.0)))
+            .join()
+            .unwrap();
+    }
+}
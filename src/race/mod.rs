@@ -0,0 +1,1274 @@
+pub mod contact;
+pub mod control;
+pub mod event;
+pub mod fuel;
+pub mod identity;
+pub mod listener;
+pub mod penalty;
+pub mod race_lines;
+pub mod spatial_index;
+pub mod stint;
+
+use crate::frame::{
+    car_status::{CarStatus, CarStatusBody},
+    event::{EventBody, EventDetails},
+    lap_data::{LapData, LapDataBody, ResultStatus},
+    motion::{CarMotion, MotionBody, PlayerMotion},
+    participants::{Participant, ParticipantsBody},
+    session::{FiaFlag, SafetyCarStatus, SessionBody, SessionType},
+    Data, Frame,
+};
+use crate::math::Vec3;
+use crate::race::contact::{Impact, ImpactDetector};
+use crate::race::control::{ControlDetector, ControlLoss, ControlLossKind, Detection};
+use crate::race::event::{lap_data_events, RaceEvent};
+use crate::race::fuel::{FuelModel, FuelProjection};
+pub use crate::race::identity::{CarId, DriverIdentity};
+use crate::race::listener::{notify, Listener, OnEvent, OnLapCompleted, OnStatusChange};
+use crate::race::penalty::{Incident, PenaltyRecord, PenaltySheet};
+pub use crate::race::race_lines::{RaceLines, Retention};
+pub use crate::race::spatial_index::SpatialIndex;
+use crate::race::stint::StintHistory;
+
+// Number of consecutive frames with the same session time after which the game is considered
+// paused. The game sends several frames with the same session time for every tick.
+const PAUSE_FRAMES: u32 = 60;
+// A session time that goes back by less than this, in seconds, is caused by frames arriving out
+// of order rather than by a flashback.
+const REORDER_TOLERANCE: f32 = 0.5;
+
+/// Period, in seconds, of which `Race` keeps the race lines by default. See `Race::with_race_lines()`.
+pub const DEFAULT_RACE_LINE_WINDOW: f32 = 600.0;
+
+/// `Race` keeps track of a session.
+pub struct Race {
+    pub status: Status,
+    pub participants: Vec<Participant>,
+    // Number of cars in the session, as received in the most recent Participants frame.
+    number_of_active_cars: usize,
+    pub race_lines: RaceLines,
+    /// Details of the session, as received in the most recent Session frame.
+    pub session: Option<SessionBody>,
+    lap_data: Vec<LapData>,
+    // The session and the session time of the most recent frame.
+    clock: Option<(u64, Timestamp)>,
+    // Vehicle indices of the players, as received in the most recent frame.
+    player_car_index: u8,
+    secondary_player_car_index: u8,
+    // Number of consecutive frames the session time didn't advance.
+    frozen_frames: u32,
+    // The status to return to when the game is resumed.
+    resume_status: Status,
+    events: Vec<(Timestamp, RaceEvent)>,
+    best_lap_time: Option<f32>,
+    stints: Vec<StintHistory>,
+    fuel: Vec<FuelModel>,
+    penalties: Vec<PenaltySheet>,
+    incidents: Vec<Incident>,
+    impacts: Vec<Impact>,
+    impact_detector: ImpactDetector,
+    control_losses: Vec<ControlLoss>,
+    control_detector: ControlDetector,
+    listeners: Vec<Box<dyn Listener>>,
+}
+
+impl Race {
+    /// Create a `Race` that keeps the race lines of the last `DEFAULT_RACE_LINE_WINDOW` seconds.
+    pub fn new() -> Self {
+        Race {
+            status: Status::Unknown,
+            participants: vec![],
+            number_of_active_cars: 0,
+            race_lines: RaceLines::with_retention(Retention::TimeWindow(DEFAULT_RACE_LINE_WINDOW)),
+            session: None,
+            lap_data: vec![],
+            clock: None,
+            player_car_index: 255,
+            secondary_player_car_index: 255,
+            frozen_frames: 0,
+            resume_status: Status::Unknown,
+            events: vec![],
+            best_lap_time: None,
+            stints: vec![StintHistory::default(); 22],
+            fuel: vec![FuelModel::default(); 22],
+            penalties: vec![PenaltySheet::default(); 22],
+            incidents: vec![],
+            impacts: vec![],
+            impact_detector: ImpactDetector::default(),
+            control_losses: vec![],
+            control_detector: ControlDetector::default(),
+            listeners: vec![],
+        }
+    }
+
+    /// Record the race lines in `race_lines`, for example to keep all locations of a session
+    /// with `RaceLines::new()` or to drop locations close to each other.
+    pub fn with_race_lines(mut self, race_lines: RaceLines) -> Self {
+        self.race_lines = race_lines;
+        self
+    }
+
+    /// Update the state of the `Race` with a `Frame`. Returns the `RaceEvent`s derived from
+    /// the frame. The registered `Listener`s are notified of these events before this method
+    /// returns. Frames that arrive slightly out of order are ignored, except Event frames. The
+    /// first frame of a new session discards the state of the previous session.
+    pub fn feed_frame(&mut self, frame: Frame) -> Vec<RaceEvent> {
+        let timestamp = frame.header.session_time;
+        // The state in a frame that arrived late is outdated, but an event is only sent once.
+        if self.arrived_late(frame.header.session_uid, timestamp)
+            && !matches!(frame.body, Data::Event(_))
+        {
+            return vec![];
+        }
+
+        let mut events = self.handle_clock(frame.header.session_uid, timestamp);
+        self.player_car_index = frame.header.player_car_index;
+        self.secondary_player_car_index = frame.header.secondary_player_car_index;
+        events.extend(self.handle_frame(frame));
+        self.publish(timestamp, &events);
+
+        events
+    }
+
+    /// Tell the `Race` that the game stopped sending frames, for example because the player quit
+    /// to the menu. The status changes to `Status::Stale` until the next frame arrives. See
+    /// `telemetry::Health` and `telemetry::Frames::recv_timeout()` to detect a stale stream.
+    pub fn mark_stale(&mut self) -> Vec<RaceEvent> {
+        let events: Vec<RaceEvent> = self.pause(Status::Stale).into_iter().collect();
+        let timestamp = self.clock.map(|(_, timestamp)| timestamp).unwrap_or(0.0);
+        self.publish(timestamp, &events);
+
+        events
+    }
+
+    // Record the events and notify the listeners.
+    fn publish(&mut self, timestamp: Timestamp, events: &[RaceEvent]) {
+        self.events
+            .extend(events.iter().map(|event| (timestamp, event.clone())));
+
+        for listener in self.listeners.iter_mut() {
+            for event in events.iter() {
+                notify(listener.as_mut(), event);
+            }
+        }
+    }
+
+    /// Return all `RaceEvent`s together with the session time they happened at. Events undone
+    /// by a flashback are removed.
+    pub fn events(&self) -> &[(Timestamp, RaceEvent)] {
+        &self.events
+    }
+
+    /// Register a `Listener` that is notified of every change of the `Race`.
+    pub fn subscribe(&mut self, listener: Box<dyn Listener>) {
+        self.listeners.push(listener);
+    }
+
+    /// Register a closure that is called for every `RaceEvent`.
+    pub fn on_event<F>(&mut self, callback: F)
+    where
+        F: FnMut(&RaceEvent) + Send + 'static,
+    {
+        self.subscribe(Box::new(OnEvent(callback)));
+    }
+
+    /// Register a closure that is called when a car completed a lap. The closure receives the
+    /// `Car`, the number of the completed lap and the lap time in seconds.
+    pub fn on_lap_completed<F>(&mut self, callback: F)
+    where
+        F: FnMut(&Car, u8, f32) + Send + 'static,
+    {
+        self.subscribe(Box::new(OnLapCompleted(callback)));
+    }
+
+    /// Register a closure that is called when the `Status` of the `Race` changed.
+    pub fn on_status_change<F>(&mut self, callback: F)
+    where
+        F: FnMut(Status, Status) + Send + 'static,
+    {
+        self.subscribe(Box::new(OnStatusChange(callback)));
+    }
+
+    // Detect a new session, a session time going backwards within a session, which happens when
+    // the player uses a flashback, and a session time that doesn't advance, which happens when
+    // the game is paused.
+    fn handle_clock(&mut self, session_uid: u64, timestamp: Timestamp) -> Vec<RaceEvent> {
+        let mut events = vec![];
+        if self.status == Status::Stale {
+            events.extend(self.resume());
+        }
+
+        if self.arrived_late(session_uid, timestamp) {
+            return events;
+        }
+
+        let previous = self.clock.replace((session_uid, timestamp));
+        match previous {
+            Some((uid, _)) if uid != session_uid => {
+                let from = self.status;
+                self.reset();
+                self.clock = Some((session_uid, timestamp));
+                if from != self.status {
+                    events.push(RaceEvent::StatusChange {
+                        from,
+                        to: self.status,
+                    });
+                }
+            }
+            Some((uid, from)) if uid == session_uid && timestamp < from => {
+                self.frozen_frames = 0;
+                self.rewind(timestamp);
+                events.push(RaceEvent::Rewind {
+                    from,
+                    to: timestamp,
+                });
+            }
+            Some((uid, from)) if uid == session_uid && timestamp == from => {
+                self.frozen_frames += 1;
+                if self.frozen_frames >= PAUSE_FRAMES {
+                    events.extend(self.pause(Status::Paused));
+                }
+            }
+            _ => {
+                self.frozen_frames = 0;
+                events.extend(self.resume());
+            }
+        }
+
+        events
+    }
+
+    // Discard the state of the previous session. The listeners and the configuration of the race
+    // lines are kept.
+    fn reset(&mut self) {
+        let listeners = std::mem::take(&mut self.listeners);
+        let mut race_lines = std::mem::take(&mut self.race_lines);
+        race_lines.clear();
+
+        *self = Race {
+            race_lines,
+            listeners,
+            ..Race::new()
+        };
+    }
+
+    // Whether a frame is older than the most recent frame of the session, but too little to be
+    // caused by a flashback.
+    fn arrived_late(&self, session_uid: u64, timestamp: Timestamp) -> bool {
+        match self.clock {
+            Some((uid, from)) => {
+                uid == session_uid && timestamp < from && from - timestamp < REORDER_TOLERANCE
+            }
+            None => false,
+        }
+    }
+
+    // Change the status to `Status::Paused` or `Status::Stale`, remembering the status to
+    // return to.
+    fn pause(&mut self, status: Status) -> Option<RaceEvent> {
+        if !self.is_paused() {
+            self.resume_status = self.status;
+        }
+        self.set_status(status)
+    }
+
+    // Return to the status from before the pause, if the race is paused.
+    fn resume(&mut self) -> Option<RaceEvent> {
+        if !self.is_paused() {
+            return None;
+        }
+        self.set_status(self.resume_status)
+    }
+
+    fn is_paused(&self) -> bool {
+        matches!(self.status, Status::Paused | Status::Stale)
+    }
+
+    // The status of the session, ignoring whether it's paused.
+    fn current_status(&self) -> Status {
+        if self.is_paused() {
+            self.resume_status
+        } else {
+            self.status
+        }
+    }
+
+    // Change the status of the session. While paused the status is changed once the game is
+    // resumed.
+    fn transition(&mut self, status: Status) -> Option<RaceEvent> {
+        if self.is_paused() {
+            self.resume_status = status;
+            return None;
+        }
+        self.set_status(status)
+    }
+
+    fn is_race(&self) -> bool {
+        match &self.session {
+            Some(session) => matches!(session.session_type, SessionType::Race | SessionType::Race2),
+            None => false,
+        }
+    }
+
+    // Derive the status from the flags and the safety car status of the most recent Session
+    // frame. Only a session that is under way is affected.
+    fn handle_session(&mut self) -> Option<RaceEvent> {
+        let session = self.session.as_ref()?;
+        let red_flag = session
+            .marshal_zones
+            .iter()
+            .any(|zone| zone.zone_flag == FiaFlag::Red);
+        let safety_car_status = session.safety_car_status;
+        let is_race = self.is_race();
+
+        let status = match self.current_status() {
+            // The session type might only become known after the session started.
+            Status::Unfolding if is_race => Status::FormationLap,
+            Status::Unfolding
+            | Status::Racing
+            | Status::SafetyCar
+            | Status::VirtualSafetyCar
+            | Status::RedFlag => {
+                if red_flag {
+                    Status::RedFlag
+                } else if !is_race {
+                    Status::Unfolding
+                } else {
+                    match safety_car_status {
+                        SafetyCarStatus::NoSafetyCar => Status::Racing,
+                        SafetyCarStatus::FullSafetyCar => Status::SafetyCar,
+                        SafetyCarStatus::VirtualSafetyCar => Status::VirtualSafetyCar,
+                    }
+                }
+            }
+            _ => return None,
+        };
+
+        self.transition(status)
+    }
+
+    // Undo everything that happened at or after `timestamp`.
+    fn rewind(&mut self, timestamp: Timestamp) {
+        self.race_lines.truncate(timestamp);
+        self.events.retain(|(time, _)| *time < timestamp);
+        self.impacts
+            .retain(|impact| impact.location.timestamp < timestamp);
+        self.control_losses
+            .retain(|loss| loss.location.timestamp < timestamp);
+        for history in self.stints.iter_mut() {
+            history.rewind(timestamp);
+        }
+        for sheet in self.penalties.iter_mut() {
+            sheet.rewind(timestamp);
+        }
+        // The fuel model undoes the laps itself once the lap number goes down.
+
+        self.incidents = self
+            .events
+            .iter()
+            .filter_map(|(_, event)| match event {
+                RaceEvent::Penalty {
+                    car,
+                    other_car: Some(other_car),
+                    penalty_type,
+                    infringement_type,
+                    time,
+                    lap_number,
+                    places_gained,
+                } => Some(Incident {
+                    car: car.clone(),
+                    other_car: other_car.clone(),
+                    penalty: PenaltyRecord::new(
+                        penalty_type.clone(),
+                        infringement_type.clone(),
+                        *time,
+                        *lap_number,
+                        *places_gained,
+                    ),
+                }),
+                _ => None,
+            })
+            .collect();
+
+        // The progress of the cars before and after the flashback can't be compared.
+        self.lap_data = vec![];
+        self.impact_detector = ImpactDetector::default();
+        self.control_detector = ControlDetector::default();
+
+        self.best_lap_time = self
+            .events
+            .iter()
+            .filter_map(|(_, event)| match event {
+                RaceEvent::OverallBestLap { lap_time, .. } => Some(*lap_time),
+                _ => None,
+            })
+            .fold(None, |best: Option<f32>, lap_time| match best {
+                Some(best) if best <= lap_time => Some(best),
+                _ => Some(lap_time),
+            });
+    }
+
+    fn handle_frame(&mut self, frame: Frame) -> Vec<RaceEvent> {
+        let timestamp: Timestamp = frame.header.session_time;
+        match frame {
+            Frame {
+                header: _,
+                body:
+                    Data::Participants(ParticipantsBody {
+                        number_of_active_cars,
+                        participants,
+                    }),
+            } => {
+                self.number_of_active_cars = number_of_active_cars as usize;
+                self.participants = participants;
+                vec![]
+            }
+            Frame {
+                header,
+                body:
+                    Data::Motion(MotionBody {
+                        car_motion,
+                        player_motion,
+                    }),
+            } => self.handle_motion(
+                timestamp,
+                car_motion,
+                header.player_car_index,
+                player_motion,
+            ),
+            Frame {
+                header: _,
+                body: Data::LapData(LapDataBody { lap_data }),
+            } => self.handle_lap_data(timestamp, lap_data),
+            Frame {
+                header: _,
+                body: Data::CarStatus(CarStatusBody { car_status }),
+            } => {
+                self.handle_car_status(timestamp, car_status);
+                vec![]
+            }
+            Frame {
+                header: _,
+                body: Data::Event(EventBody { details, .. }),
+            } => self.handle_event(timestamp, details),
+            Frame {
+                header: _,
+                body: Data::Session(session),
+            } => {
+                let paused = session.game_paused != 0;
+                self.session = Some(session);
+
+                let mut events: Vec<RaceEvent> = self.handle_session().into_iter().collect();
+                if paused {
+                    events.extend(self.pause(Status::Paused));
+                }
+                events
+            }
+        }
+    }
+
+    /// Resolve the vehicle index used by the F1 2020 API to a `Car`. Returns `None` if no
+    /// participant is known for that index.
+    pub fn car(&self, index: u8) -> Option<Car> {
+        self.participants
+            .get(index as usize)
+            .map(|participant| Car {
+                index,
+                participant: participant.clone(),
+            })
+    }
+
+    /// Return the car of the player. Returns `None` while spectating or if the car isn't known
+    /// yet.
+    pub fn player(&self) -> Option<Car> {
+        self.player_car(self.player_car_index)
+    }
+
+    /// Return the car of the second player in split screen mode. Returns `None` if there's no
+    /// second player.
+    pub fn secondary_player(&self) -> Option<Car> {
+        self.player_car(self.secondary_player_car_index)
+    }
+
+    // The API uses the index 255 for a player that doesn't exist.
+    fn player_car(&self, index: u8) -> Option<Car> {
+        let spectating = match &self.session {
+            Some(session) => session.is_spectating != 0,
+            None => false,
+        };
+        if index == 255 || spectating {
+            return None;
+        }
+
+        self.car(index)
+    }
+
+    fn handle_event(&mut self, timestamp: Timestamp, details: EventDetails) -> Vec<RaceEvent> {
+        match details {
+            EventDetails::SessionStarted => {
+                let status = if self.is_race() {
+                    Status::FormationLap
+                } else {
+                    Status::Unfolding
+                };
+                self.transition(status).into_iter().collect()
+            }
+            EventDetails::ChequeredFlag => {
+                self.transition(Status::ChequeredFlag).into_iter().collect()
+            }
+            EventDetails::RaceWinner => self
+                .transition(Status::RaceWinnerDeclared)
+                .into_iter()
+                .collect(),
+            EventDetails::SessionEnded => self.transition(Status::Finished).into_iter().collect(),
+            EventDetails::Retirement { vehicle_id } => self
+                .car(vehicle_id)
+                .map(|car| RaceEvent::Retirement { car })
+                .into_iter()
+                .collect(),
+            EventDetails::Penalty {
+                penalty_type,
+                infringement_type,
+                vehicle_id,
+                other_vehicle_id,
+                time,
+                lap_number,
+                places_gained,
+            } => {
+                let car = match self.car(vehicle_id) {
+                    Some(car) => car,
+                    None => return vec![],
+                };
+                let other_car = self.car(other_vehicle_id);
+                let penalty = PenaltyRecord::new(
+                    penalty_type.clone(),
+                    infringement_type.clone(),
+                    time,
+                    lap_number,
+                    places_gained,
+                );
+
+                if let Some(other_car) = other_car.clone() {
+                    self.incidents.push(Incident {
+                        car: car.clone(),
+                        other_car,
+                        penalty: penalty.clone(),
+                    });
+                }
+                if let Some(sheet) = self.penalties.get_mut(vehicle_id as usize) {
+                    sheet.add(timestamp, penalty);
+                }
+
+                vec![RaceEvent::Penalty {
+                    car,
+                    other_car,
+                    penalty_type,
+                    infringement_type,
+                    time,
+                    lap_number,
+                    places_gained,
+                }]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Return the `PenaltySheet` of `car`.
+    pub fn penalty_sheet(&self, car: CarId) -> Option<&PenaltySheet> {
+        self.index_of(car)
+            .and_then(|index| self.penalties.get(index))
+    }
+
+    /// Return all penalties involving two cars, in the order they were given.
+    pub fn incidents(&self) -> &[Incident] {
+        &self.incidents
+    }
+
+    /// Return all probable contacts and wall hits, in the order they happened.
+    pub fn impacts(&self) -> &[Impact] {
+        &self.impacts
+    }
+
+    /// Return all spins, slides and cars driving the wrong way, in the order they happened.
+    pub fn control_losses(&self) -> &[ControlLoss] {
+        &self.control_losses
+    }
+
+    /// Return the stints and pit stops of `car`.
+    pub fn stint_history(&self, car: CarId) -> Option<&StintHistory> {
+        self.index_of(car).and_then(|index| self.stints.get(index))
+    }
+
+    /// Return the `FuelModel` of `car`.
+    pub fn fuel_model(&self, car: CarId) -> Option<&FuelModel> {
+        self.index_of(car).and_then(|index| self.fuel.get(index))
+    }
+
+    /// Project the fuel of `car` at the finish, based on the number of laps of the session and
+    /// the progress of the car. Returns `None` until the session, the lap data of the car and
+    /// its average consumption are known.
+    pub fn fuel_projection(&self, car: CarId) -> Option<FuelProjection> {
+        let index = self.index_of(car)?;
+        let laps_remaining = self.laps_remaining(index)?;
+
+        self.fuel.get(index)?.projection(laps_remaining)
+    }
+
+    // Laps the car at `index` still has to drive, including the rest of its current lap.
+    fn laps_remaining(&self, index: usize) -> Option<f32> {
+        let session = self.session.as_ref()?;
+        let lap = self.lap_data.get(index)?;
+        if session.total_laps == 0 {
+            return None;
+        }
+
+        // The lap distance is negative until the car crossed the line for the first time.
+        let progress = if session.track_length > 0 {
+            (lap.lap_distance / session.track_length as f32).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let completed = lap.current_lap_num.saturating_sub(1) as f32 + progress;
+
+        Some((session.total_laps as f32 - completed).max(0.0))
+    }
+
+    // Whether the car at `index` takes part in the session. Until its lap data is known, the
+    // first `number_of_active_cars` cars are.
+    fn is_active(&self, index: usize) -> bool {
+        match self.lap_data.get(index) {
+            Some(lap) => !matches!(
+                lap.result_status,
+                ResultStatus::Invalid | ResultStatus::Inactive
+            ),
+            None => index < self.number_of_active_cars,
+        }
+    }
+
+    // Find the vehicle index of `car`.
+    fn index_of(&self, car: CarId) -> Option<usize> {
+        self.participants
+            .iter()
+            .enumerate()
+            .position(|(index, participant)| CarId::new(index as u8, participant) == car)
+    }
+
+    fn handle_car_status(&mut self, timestamp: Timestamp, car_status: Vec<CarStatus>) {
+        for (history, status) in self.stints.iter_mut().zip(car_status.iter()) {
+            history.update_car_status(timestamp, status);
+        }
+        for (model, status) in self.fuel.iter_mut().zip(car_status.iter()) {
+            model.update_car_status(status);
+        }
+    }
+
+    fn handle_lap_data(&mut self, timestamp: Timestamp, lap_data: Vec<LapData>) -> Vec<RaceEvent> {
+        for (history, lap) in self.stints.iter_mut().zip(lap_data.iter()) {
+            history.update_lap_data(timestamp, lap);
+        }
+        for (model, lap) in self.fuel.iter_mut().zip(lap_data.iter()) {
+            model.update_lap(lap.current_lap_num);
+        }
+        for (sheet, lap) in self.penalties.iter_mut().zip(lap_data.iter()) {
+            sheet.update_lap_data(timestamp, lap);
+        }
+
+        let detections = self.control_detector.update_lap_data(&lap_data);
+
+        let previous = std::mem::replace(&mut self.lap_data, lap_data);
+        let mut best_lap_time = self.best_lap_time;
+        let mut events = lap_data_events(&previous, &self.lap_data, &mut best_lap_time, |index| {
+            self.car(index as u8)
+        });
+        self.best_lap_time = best_lap_time;
+        events.extend(self.handle_control_losses(detections));
+
+        // The lap timer starts when the lights go out. Until then the cars are on the
+        // formation lap.
+        if self.current_status() == Status::FormationLap
+            && self.lap_data.iter().any(|lap| lap.current_lap_time > 0.0)
+        {
+            events.extend(self.transition(Status::Racing));
+        }
+
+        events
+    }
+
+    // Change the status of the race. Returns a `RaceEvent` if the status actually changed.
+    fn set_status(&mut self, status: Status) -> Option<RaceEvent> {
+        if self.status == status {
+            return None;
+        }
+
+        let from = std::mem::replace(&mut self.status, status);
+        Some(RaceEvent::StatusChange { from, to: status })
+    }
+
+    fn handle_motion(
+        &mut self,
+        timestamp: Timestamp,
+        details: [CarMotion; 22],
+        player_car_index: u8,
+        player_motion: PlayerMotion,
+    ) -> Vec<RaceEvent> {
+        // Motion frames received before the participants are known can't be attributed to a
+        // car.
+        let mut locations = vec![];
+        for (index, (motion, participant)) in
+            details.iter().zip(self.participants.iter()).enumerate()
+        {
+            let point = SpatialLocation {
+                car: CarId::new(index as u8, participant),
+                timestamp,
+                coords: motion.position().to_map_with_elevation(),
+            };
+
+            locations.push(point);
+            self.race_lines.append(point);
+        }
+
+        let active: Vec<bool> = (0..locations.len())
+            .map(|index| self.is_active(index))
+            .collect();
+        let detections =
+            self.impact_detector
+                .update(timestamp, &details[..locations.len()], &active);
+
+        let mut events = vec![];
+        for detection in detections {
+            let car = match self.car(detection.index as u8) {
+                Some(car) => car,
+                None => continue,
+            };
+            let other_car = detection.other.and_then(|index| self.car(index as u8));
+            let location = locations[detection.index];
+            let severity = detection.severity;
+
+            events.push(match other_car.clone() {
+                Some(other_car) => RaceEvent::Contact {
+                    car: car.clone(),
+                    other_car,
+                    location,
+                    severity,
+                },
+                None => RaceEvent::WallHit {
+                    car: car.clone(),
+                    location,
+                    severity,
+                },
+            });
+            self.impacts.push(Impact {
+                car,
+                other_car,
+                location,
+                severity,
+            });
+        }
+
+        let detections = self.control_detector.update_motion(
+            timestamp,
+            &details[..locations.len()],
+            player_car_index as usize,
+            &player_motion,
+        );
+        events.extend(self.handle_control_losses(detections));
+
+        events
+    }
+
+    fn handle_control_losses(&mut self, detections: Vec<Detection>) -> Vec<RaceEvent> {
+        let mut events = vec![];
+        for detection in detections {
+            let car = match self.car(detection.index as u8) {
+                Some(car) => car,
+                None => continue,
+            };
+            let location = SpatialLocation {
+                car: car.id(),
+                timestamp: detection.timestamp,
+                coords: detection.position.to_map_with_elevation(),
+            };
+            let lap_distance = detection.lap_distance;
+
+            events.push(match detection.kind {
+                ControlLossKind::Spin => RaceEvent::Spin {
+                    car: car.clone(),
+                    location,
+                    lap_distance,
+                },
+                ControlLossKind::Slide { slip_angle } => RaceEvent::Slide {
+                    car: car.clone(),
+                    location,
+                    lap_distance,
+                    slip_angle,
+                },
+                ControlLossKind::WrongWay => RaceEvent::WrongWay {
+                    car: car.clone(),
+                    location,
+                    lap_distance,
+                },
+            });
+            self.control_losses.push(ControlLoss {
+                car,
+                kind: detection.kind,
+                location,
+                lap_distance,
+            });
+        }
+
+        events
+    }
+}
+
+impl Default for Race {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `Car` is a vehicle taking part in the race, together with the `Participant` driving it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Car {
+    /// Index of the car in the frames of the F1 2020 API.
+    pub index: u8,
+    pub participant: Participant,
+}
+
+impl Car {
+    /// The stable identifier of the car. See `CarId`.
+    pub fn id(&self) -> CarId {
+        CarId::new(self.index, &self.participant)
+    }
+
+    pub fn identity(&self) -> DriverIdentity {
+        DriverIdentity::new(self.index, &self.participant)
+    }
+}
+
+/// `SpatialLocation` contains the physical location of a car at a certain point in time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpatialLocation {
+    pub car: CarId,
+    pub timestamp: Timestamp,
+    /// Location as `(x, z, y)`: the location on the 2D map followed by the elevation. Note
+    /// that the order differs from the `(x, y, z)` order of the game world. See `Vec3` for
+    /// the coordinate frames.
+    pub coords: (f32, f32, f32),
+}
+
+impl SpatialLocation {
+    /// Location in the game world.
+    pub fn position(&self) -> Vec3 {
+        Vec3::from_map_with_elevation(self.coords)
+    }
+
+    /// Location on the 2D map.
+    pub fn map_point(&self) -> (f32, f32) {
+        (self.coords.0, self.coords.1)
+    }
+}
+
+pub type Timestamp = f32;
+
+/// Status of a `Race`.
+#[derive(Debug, Clone, PartialEq, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Status {
+    // Session has ended.
+    Finished,
+    // Session other than a race is on going.
+    Unfolding,
+    // Cars drive the formation lap before the start of a race.
+    FormationLap,
+    // Race is on going under green flag conditions.
+    Racing,
+    // Safety car is deployed.
+    SafetyCar,
+    // Virtual safety car is deployed.
+    VirtualSafetyCar,
+    // Session is suspended.
+    RedFlag,
+    // Chequered flag has been waved.
+    ChequeredFlag,
+    // Winner of the race has been declared.
+    RaceWinnerDeclared,
+    // Status of the race is unknown.
+    Unknown,
+    // The game is paused: frames keep arriving, but the session time doesn't advance.
+    Paused,
+    // The game stopped sending frames.
+    Stale,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::{
+        car_status, frame, lap_data, lap_data_frame, motion_frame, participants_frame,
+    };
+    use crate::frame::{
+        car_status::{CarStatusBody, TyreCompound},
+        event::{EventBody, EventDetails, InfringementType, PenaltyType},
+        session::{
+            Circuit, FiaFlag, Formula, MarshalZone, SafetyCarStatus, SessionBody, SessionType,
+            Weather,
+        },
+        Data, Frame,
+    };
+    use crate::race::{
+        event::RaceEvent, fuel::FuelProjection, Race, RaceLines, Retention, Status,
+        DEFAULT_RACE_LINE_WINDOW, PAUSE_FRAMES,
+    };
+
+    fn event(session_time: f32, details: EventDetails) -> Frame {
+        frame(
+            session_time,
+            Data::Event(EventBody {
+                code: String::new(),
+                details,
+            }),
+        )
+    }
+
+    fn session(session_time: f32, safety_car_status: SafetyCarStatus, flag: FiaFlag) -> Frame {
+        frame(
+            session_time,
+            Data::Session(SessionBody {
+                weather: Weather::Clear,
+                track_temperature: 30,
+                air_temperature: 20,
+                total_laps: 5,
+                track_length: 5000,
+                session_type: SessionType::Race,
+                track: Circuit::Zandvoort,
+                formula: Formula::F1Modern,
+                session_time_left: 3600,
+                session_duration: 3600,
+                pit_speed_limit: 80,
+                game_paused: 0,
+                is_spectating: 0,
+                spectator_car_index: 255,
+                sli_pro_native_support: 0,
+                number_of_marshal_zones: 1,
+                marshal_zones: vec![MarshalZone {
+                    zone_start: 0.0,
+                    zone_flag: flag,
+                }],
+                safety_car_status,
+                network_game: 0,
+                number_of_weather_forecast_samples: 0,
+                weather_forecast_samples: vec![],
+            }),
+        )
+    }
+
+    #[test]
+    fn test_status() {
+        let mut race = Race::new();
+        race.feed_frame(session(0.0, SafetyCarStatus::NoSafetyCar, FiaFlag::None));
+        race.feed_frame(event(0.1, EventDetails::SessionStarted));
+        assert_eq!(race.status, Status::FormationLap);
+
+        // A safety car on the formation lap doesn't start the race.
+        race.feed_frame(session(0.2, SafetyCarStatus::FullSafetyCar, FiaFlag::None));
+        assert_eq!(race.status, Status::FormationLap);
+
+        // The race starts when the lap timer starts.
+        let mut lap = lap_data(1);
+        race.feed_frame(lap_data_frame(0.3, vec![lap]));
+        assert_eq!(race.status, Status::FormationLap);
+        lap.current_lap_time = 0.2;
+        race.feed_frame(lap_data_frame(0.5, vec![lap]));
+        assert_eq!(race.status, Status::Racing);
+
+        let virtual_safety_car = SafetyCarStatus::VirtualSafetyCar;
+        race.feed_frame(session(1.0, virtual_safety_car, FiaFlag::None));
+        assert_eq!(race.status, Status::VirtualSafetyCar);
+        race.feed_frame(session(2.0, SafetyCarStatus::FullSafetyCar, FiaFlag::Red));
+        assert_eq!(race.status, Status::RedFlag);
+        race.feed_frame(session(3.0, SafetyCarStatus::NoSafetyCar, FiaFlag::Green));
+        assert_eq!(race.status, Status::Racing);
+
+        race.feed_frame(event(4.0, EventDetails::ChequeredFlag));
+        assert_eq!(race.status, Status::ChequeredFlag);
+        race.feed_frame(session(5.0, SafetyCarStatus::NoSafetyCar, FiaFlag::None));
+        assert_eq!(race.status, Status::ChequeredFlag);
+
+        assert_eq!(race.mark_stale().len(), 1);
+        assert_eq!(race.status, Status::Stale);
+        race.feed_frame(event(6.0, EventDetails::RaceWinner));
+        assert_eq!(race.status, Status::RaceWinnerDeclared);
+        race.feed_frame(event(7.0, EventDetails::SessionEnded));
+        assert_eq!(race.status, Status::Finished);
+    }
+
+    #[test]
+    fn test_status_change() {
+        let mut race = Race::new();
+        let events = race.feed_frame(session(0.0, SafetyCarStatus::NoSafetyCar, FiaFlag::None));
+        assert!(events.is_empty());
+
+        let events = race.feed_frame(event(0.1, EventDetails::SessionStarted));
+        assert_eq!(
+            events,
+            vec![RaceEvent::StatusChange {
+                from: Status::Unknown,
+                to: Status::FormationLap,
+            }]
+        );
+
+        let events = race.feed_frame(event(0.2, EventDetails::SessionStarted));
+        assert!(events.is_empty());
+        assert_eq!(race.events().len(), 1);
+    }
+
+    #[test]
+    fn test_players() {
+        let mut race = Race::new();
+        race.feed_frame(participants_frame(0.0));
+        assert_eq!(race.player().unwrap().index, 0);
+        assert_eq!(race.secondary_player(), None);
+
+        // Split screen with the second player in the second car.
+        let mut split_screen = participants_frame(0.1);
+        split_screen.header.secondary_player_car_index = 1;
+        race.feed_frame(split_screen);
+        assert_eq!(race.player().unwrap().index, 0);
+        assert_eq!(race.secondary_player().unwrap().index, 1);
+
+        // The index 255 means there's no player.
+        let mut no_player = participants_frame(0.2);
+        no_player.header.player_car_index = 255;
+        race.feed_frame(no_player);
+        assert_eq!(race.player(), None);
+
+        let mut spectating = session(0.3, SafetyCarStatus::NoSafetyCar, FiaFlag::None);
+        spectating.header.secondary_player_car_index = 1;
+        if let Data::Session(session) = &mut spectating.body {
+            session.is_spectating = 1;
+            session.spectator_car_index = 1;
+        }
+        race.feed_frame(spectating);
+        assert_eq!(race.player(), None);
+        assert_eq!(race.secondary_player(), None);
+    }
+
+    #[test]
+    fn test_pause() {
+        let mut race = Race::new();
+        race.feed_frame(session(0.0, SafetyCarStatus::NoSafetyCar, FiaFlag::None));
+        race.feed_frame(event(0.1, EventDetails::SessionStarted));
+
+        // The game sends several frames per tick, a few frames with the same session time
+        // aren't a pause.
+        for _ in 0..PAUSE_FRAMES {
+            race.feed_frame(lap_data_frame(1.0, vec![]));
+        }
+        assert_eq!(race.status, Status::FormationLap);
+
+        let events = race.feed_frame(lap_data_frame(1.0, vec![]));
+        assert_eq!(
+            events,
+            vec![RaceEvent::StatusChange {
+                from: Status::FormationLap,
+                to: Status::Paused,
+            }]
+        );
+
+        let events = race.feed_frame(lap_data_frame(1.1, vec![]));
+        assert_eq!(
+            events,
+            vec![RaceEvent::StatusChange {
+                from: Status::Paused,
+                to: Status::FormationLap,
+            }]
+        );
+
+        // The game stopped sending frames. The stream resumes with the next frame.
+        race.mark_stale();
+        assert_eq!(race.status, Status::Stale);
+        assert!(race.mark_stale().is_empty());
+        race.feed_frame(lap_data_frame(1.2, vec![]));
+        assert_eq!(race.status, Status::FormationLap);
+    }
+
+    #[test]
+    fn test_race_lines() {
+        let mut race = Race::new();
+        race.feed_frame(participants_frame(0.0));
+        race.feed_frame(motion_frame(1.0));
+        race.feed_frame(motion_frame(2.0));
+        assert_eq!(race.race_lines.len(), 4);
+
+        // By default only the race lines of the last 10 minutes are kept.
+        race.feed_frame(motion_frame(DEFAULT_RACE_LINE_WINDOW + 1.5));
+        assert_eq!(race.race_lines.len(), 4);
+
+        let race_lines = RaceLines::with_retention(Retention::Locations(1));
+        let mut race = Race::new().with_race_lines(race_lines);
+        race.feed_frame(participants_frame(0.0));
+        race.feed_frame(motion_frame(1.0));
+        race.feed_frame(motion_frame(2.0));
+        assert_eq!(race.race_lines.len(), 2);
+    }
+
+    #[test]
+    fn test_fuel_projection() {
+        let mut race = Race::new();
+        race.feed_frame(participants_frame(0.0));
+        race.feed_frame(session(0.0, SafetyCarStatus::NoSafetyCar, FiaFlag::None));
+        let car = race.car(0).unwrap().id();
+
+        let car_status_frame = |session_time, fuel_in_tank| {
+            let mut car_status = car_status();
+            car_status.fuel_in_tank = fuel_in_tank;
+            frame(
+                session_time,
+                Data::CarStatus(CarStatusBody {
+                    car_status: vec![car_status; 2],
+                }),
+            )
+        };
+        let lap_frame = |session_time, current_lap_num, lap_distance| {
+            let mut lap = lap_data(1);
+            lap.current_lap_num = current_lap_num;
+            lap.lap_distance = lap_distance;
+            lap_data_frame(session_time, vec![lap, lap_data(2)])
+        };
+
+        race.feed_frame(car_status_frame(1.0, 30.0));
+        race.feed_frame(lap_frame(1.0, 1, 0.0));
+        assert_eq!(race.fuel_projection(car), None);
+
+        // Halfway through lap 2 of 5, after using 2 kg on lap 1.
+        race.feed_frame(car_status_frame(90.0, 28.0));
+        race.feed_frame(lap_frame(90.0, 2, 2500.0));
+        assert_eq!(
+            race.fuel_projection(car),
+            Some(FuelProjection {
+                laps_remaining: 3.5,
+                fuel_at_finish: 21.0,
+                delta: 10.5,
+            })
+        );
+    }
+
+    #[test]
+    fn test_rewind() {
+        let mut race = Race::new();
+        race.feed_frame(participants_frame(0.0));
+        let car = race.car(1).unwrap().id();
+
+        let car_status_frame = |session_time, compound, fuel_in_tank| {
+            let mut car_status = car_status();
+            car_status.actual_tyre_compound = compound;
+            car_status.fuel_in_tank = fuel_in_tank;
+            frame(
+                session_time,
+                Data::CarStatus(CarStatusBody {
+                    car_status: vec![car_status; 2],
+                }),
+            )
+        };
+        let lap_frame = |session_time, current_lap_num| {
+            let mut first = lap_data(1);
+            first.current_lap_num = current_lap_num;
+            let mut second = lap_data(2);
+            second.current_lap_num = current_lap_num;
+            lap_data_frame(session_time, vec![first, second])
+        };
+
+        race.feed_frame(car_status_frame(1.0, TyreCompound::C3, 30.0));
+        race.feed_frame(lap_frame(1.0, 1));
+        race.feed_frame(car_status_frame(8.0, TyreCompound::C2, 28.0));
+        race.feed_frame(lap_frame(8.0, 2));
+        race.feed_frame(event(
+            9.0,
+            EventDetails::Penalty {
+                penalty_type: PenaltyType::DriveThrough,
+                infringement_type: InfringementType::SmallCollision,
+                vehicle_id: 1,
+                other_vehicle_id: 0,
+                time: 255,
+                lap_number: 2,
+                places_gained: 0,
+            },
+        ));
+
+        // A frame that arrives out of order isn't a flashback.
+        assert!(race.feed_frame(lap_frame(8.9, 2)).is_empty());
+        assert_eq!(race.incidents().len(), 1);
+        assert_eq!(race.penalty_sheet(car).unwrap().drive_throughs, 1);
+        assert_eq!(race.stint_history(car).unwrap().stints.len(), 2);
+        assert_eq!(race.fuel_model(car).unwrap().laps.len(), 1);
+
+        let events = race.feed_frame(lap_frame(5.0, 1));
+        assert_eq!(events, vec![RaceEvent::Rewind { from: 9.0, to: 5.0 }]);
+        assert!(!race
+            .events()
+            .iter()
+            .any(|(_, event)| matches!(event, RaceEvent::Penalty { .. })));
+        assert!(race.incidents().is_empty());
+        assert!(race.penalty_sheet(car).unwrap().penalties.is_empty());
+        assert_eq!(race.stint_history(car).unwrap().stints.len(), 1);
+        assert!(race.fuel_model(car).unwrap().laps.is_empty());
+    }
+
+    #[test]
+    fn test_new_session() {
+        let mut race = Race::new();
+        race.feed_frame(participants_frame(0.0));
+        race.feed_frame(session(0.0, SafetyCarStatus::NoSafetyCar, FiaFlag::None));
+        race.feed_frame(event(0.1, EventDetails::SessionStarted));
+        let car = race.car(0).unwrap().id();
+
+        let mut lap = lap_data(1);
+        race.feed_frame(frame(
+            1.0,
+            Data::CarStatus(CarStatusBody {
+                car_status: vec![car_status(); 2],
+            }),
+        ));
+        race.feed_frame(lap_data_frame(1.0, vec![lap, lap_data(2)]));
+        lap.current_lap_num = 2;
+        lap.current_lap_time = 0.5;
+        lap.last_lap_time = 91.5;
+        race.feed_frame(lap_data_frame(92.0, vec![lap, lap_data(2)]));
+        race.feed_frame(motion_frame(92.5));
+        race.feed_frame(event(
+            93.0,
+            EventDetails::Penalty {
+                penalty_type: PenaltyType::DriveThrough,
+                infringement_type: InfringementType::SmallCollision,
+                vehicle_id: 0,
+                other_vehicle_id: 1,
+                time: 255,
+                lap_number: 2,
+                places_gained: 0,
+            },
+        ));
+        assert_eq!(race.status, Status::Racing);
+        assert_eq!(race.incidents().len(), 1);
+        assert_eq!(race.fuel_model(car).unwrap().laps.len(), 1);
+
+        // The first frame of the next session starts over, even though its session time is
+        // earlier.
+        let mut next = participants_frame(0.5);
+        next.header.session_uid = 2;
+        let events = race.feed_frame(next);
+        let status_change = RaceEvent::StatusChange {
+            from: Status::Racing,
+            to: Status::Unknown,
+        };
+        assert_eq!(events, vec![status_change.clone()]);
+        assert_eq!(race.events(), &[(0.5, status_change)]);
+        assert_eq!(race.participants.len(), 2);
+        assert!(race.session.is_none());
+        assert!(race.race_lines.is_empty());
+        assert!(race.incidents().is_empty());
+        assert!(race.penalty_sheet(car).unwrap().penalties.is_empty());
+        assert!(race.stint_history(car).unwrap().stints.is_empty());
+        assert!(race.fuel_model(car).unwrap().laps.is_empty());
+    }
+}
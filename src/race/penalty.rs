@@ -0,0 +1,333 @@
+use crate::frame::{
+    event::{InfringementType, PenaltyType},
+    lap_data::{LapData, PitStatus},
+};
+use crate::race::{Car, Timestamp};
+
+/// A penalty given to a car.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PenaltyRecord {
+    pub penalty_type: PenaltyType,
+    pub infringement_type: InfringementType,
+    /// Time in seconds, if the penalty comes with a time.
+    pub time: Option<u8>,
+    /// Lap number when the foul was committed.
+    pub lap: u8,
+    /// Number of positions gained by the foul.
+    pub places_gained: u8,
+}
+
+impl PenaltyRecord {
+    // Create a record from the details of a Penalty event, in which a `time` of 255 means the
+    // penalty doesn't come with a time.
+    pub(crate) fn new(
+        penalty_type: PenaltyType,
+        infringement_type: InfringementType,
+        time: u8,
+        lap: u8,
+        places_gained: u8,
+    ) -> Self {
+        PenaltyRecord {
+            penalty_type,
+            infringement_type,
+            time: if time == 255 { None } else { Some(time) },
+            lap,
+            places_gained,
+        }
+    }
+}
+
+/// `PenaltySheet` summarizes the penalties a car received during a session.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PenaltySheet {
+    /// All penalties, in the order they were given.
+    pub penalties: Vec<PenaltyRecord>,
+    pub warnings: u32,
+    /// Number of infringements for cutting corners or running wide.
+    pub corner_cutting: u32,
+    /// Sum of all time penalties in seconds.
+    pub time_penalties: u32,
+    pub drive_throughs: u32,
+    pub drive_throughs_served: u32,
+    pub stop_gos: u32,
+    pub stop_gos_served: u32,
+    // Session time at which each penalty was given.
+    given_at: Vec<Timestamp>,
+    // Session time at which each drive-through or stop-go penalty was served.
+    served: Vec<(Timestamp, PenaltyType)>,
+    pit_status: Option<PitStatus>,
+    // Whether the car stopped in its pit box during the current visit to the pit lane.
+    stopped: bool,
+}
+
+impl PenaltySheet {
+    /// Number of drive-through penalties that haven't been served yet.
+    pub fn unserved_drive_throughs(&self) -> u32 {
+        self.drive_throughs
+            .saturating_sub(self.drive_throughs_served)
+    }
+
+    /// Number of stop-go penalties that haven't been served yet.
+    pub fn unserved_stop_gos(&self) -> u32 {
+        self.stop_gos.saturating_sub(self.stop_gos_served)
+    }
+
+    pub(crate) fn add(&mut self, timestamp: Timestamp, penalty: PenaltyRecord) {
+        match penalty.penalty_type {
+            PenaltyType::Warning => self.warnings += 1,
+            PenaltyType::DriveThrough => self.drive_throughs += 1,
+            PenaltyType::StopGo => self.stop_gos += 1,
+            PenaltyType::TimePenalty => {
+                self.time_penalties += penalty.time.unwrap_or_default() as u32
+            }
+            _ => {}
+        }
+
+        if is_corner_cutting(&penalty.infringement_type) {
+            self.corner_cutting += 1;
+        }
+
+        self.penalties.push(penalty);
+        self.given_at.push(timestamp);
+    }
+
+    // The game doesn't report when a penalty is served. A penalty is considered served when the
+    // car leaves the pit lane while the penalty is outstanding. A stop in the pit box serves a
+    // stop-go penalty, driving through the pit lane serves a drive-through penalty.
+    pub(crate) fn update_lap_data(&mut self, timestamp: Timestamp, lap_data: &LapData) {
+        let previous = self.pit_status.replace(lap_data.pit_status);
+        match (previous, lap_data.pit_status) {
+            (_, PitStatus::InPitArea) => self.stopped = true,
+            (Some(PitStatus::Pitting), PitStatus::None)
+            | (Some(PitStatus::InPitArea), PitStatus::None) => {
+                if self.stopped && self.unserved_stop_gos() > 0 {
+                    self.serve(timestamp, PenaltyType::StopGo);
+                } else if self.unserved_drive_throughs() > 0 {
+                    self.serve(timestamp, PenaltyType::DriveThrough);
+                }
+                self.stopped = false;
+            }
+            _ => {}
+        }
+    }
+
+    // Undo the penalties given and served at or after `timestamp`. The state of the pit lane is
+    // forgotten, the next Lap Data frame tells where the car is after the flashback.
+    pub(crate) fn rewind(&mut self, timestamp: Timestamp) {
+        let mut sheet = PenaltySheet::default();
+        for (given_at, penalty) in self.given_at.iter().zip(self.penalties.iter()) {
+            if *given_at < timestamp {
+                sheet.add(*given_at, penalty.clone());
+            }
+        }
+        for (served_at, penalty_type) in self.served.iter() {
+            if *served_at < timestamp {
+                sheet.serve(*served_at, penalty_type.clone());
+            }
+        }
+
+        *self = sheet;
+    }
+
+    fn serve(&mut self, timestamp: Timestamp, penalty_type: PenaltyType) {
+        match penalty_type {
+            PenaltyType::DriveThrough => self.drive_throughs_served += 1,
+            PenaltyType::StopGo => self.stop_gos_served += 1,
+            _ => return,
+        }
+        self.served.push((timestamp, penalty_type));
+    }
+}
+
+/// `Incident` is a penalty involving two cars.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Incident {
+    /// The car that was penalized.
+    pub car: Car,
+    pub other_car: Car,
+    pub penalty: PenaltyRecord,
+}
+
+fn is_corner_cutting(infringement_type: &InfringementType) -> bool {
+    matches!(
+        infringement_type,
+        InfringementType::CornerCuttingGainedTime
+            | InfringementType::CornerCuttingOvertakeSingle
+            | InfringementType::CornerCuttingOvertakeMultiple
+            | InfringementType::LapInvalidatedCornerCutting
+            | InfringementType::LapInvalidatedRunningWide
+            | InfringementType::CornerCuttingRanWideGainedTimeMinor
+            | InfringementType::CornerCuttingRanWideGainedTimeSignificant
+            | InfringementType::CornerCuttingRanWideGainedTimeExtreme
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::{frame, lap_data, participants_frame};
+    use crate::frame::{
+        event::{EventBody, EventDetails, InfringementType, PenaltyType},
+        lap_data::PitStatus,
+        Data,
+    };
+    use crate::race::{
+        penalty::{PenaltyRecord, PenaltySheet},
+        Race,
+    };
+
+    fn penalty(penalty_type: PenaltyType, infringement_type: InfringementType) -> PenaltyRecord {
+        PenaltyRecord {
+            penalty_type,
+            infringement_type,
+            time: None,
+            lap: 1,
+            places_gained: 0,
+        }
+    }
+
+    fn drive(sheet: &mut PenaltySheet, pit_statuses: &[PitStatus]) {
+        for pit_status in pit_statuses {
+            let mut lap_data = lap_data(1);
+            lap_data.pit_status = *pit_status;
+            sheet.update_lap_data(0.0, &lap_data);
+        }
+    }
+
+    #[test]
+    fn test_drive_through() {
+        let mut sheet = PenaltySheet::default();
+        sheet.add(
+            0.0,
+            penalty(PenaltyType::DriveThrough, InfringementType::PitLaneSpeeding),
+        );
+        assert_eq!(sheet.unserved_drive_throughs(), 1);
+
+        drive(
+            &mut sheet,
+            &[PitStatus::None, PitStatus::Pitting, PitStatus::None],
+        );
+        assert_eq!(sheet.drive_throughs_served, 1);
+        assert_eq!(sheet.unserved_drive_throughs(), 0);
+    }
+
+    #[test]
+    fn test_stop_go() {
+        let mut sheet = PenaltySheet::default();
+        sheet.add(
+            0.0,
+            penalty(PenaltyType::StopGo, InfringementType::IgnoringDriveThrough),
+        );
+
+        // Driving through the pit lane doesn't serve a stop-go penalty.
+        drive(
+            &mut sheet,
+            &[PitStatus::None, PitStatus::Pitting, PitStatus::None],
+        );
+        assert_eq!(sheet.unserved_stop_gos(), 1);
+
+        drive(
+            &mut sheet,
+            &[
+                PitStatus::Pitting,
+                PitStatus::InPitArea,
+                PitStatus::Pitting,
+                PitStatus::None,
+            ],
+        );
+        assert_eq!(sheet.stop_gos_served, 1);
+        assert_eq!(sheet.unserved_stop_gos(), 0);
+    }
+
+    #[test]
+    fn test_counters() {
+        let mut sheet = PenaltySheet::default();
+        let mut time_penalty = penalty(
+            PenaltyType::TimePenalty,
+            InfringementType::CornerCuttingGainedTime,
+        );
+        time_penalty.time = Some(5);
+        sheet.add(0.0, time_penalty.clone());
+        time_penalty.time = Some(10);
+        time_penalty.infringement_type = InfringementType::SmallCollision;
+        sheet.add(0.0, time_penalty);
+        sheet.add(
+            0.0,
+            penalty(
+                PenaltyType::Warning,
+                InfringementType::LapInvalidatedRunningWide,
+            ),
+        );
+
+        assert_eq!(sheet.penalties.len(), 3);
+        assert_eq!(sheet.time_penalties, 15);
+        assert_eq!(sheet.corner_cutting, 2);
+        assert_eq!(sheet.warnings, 1);
+    }
+
+    #[test]
+    fn test_incidents() {
+        let mut race = Race::new();
+        race.feed_frame(participants_frame(0.0));
+
+        let event = |session_time, other_vehicle_id| {
+            frame(
+                session_time,
+                Data::Event(EventBody {
+                    code: "PENA".to_string(),
+                    details: EventDetails::Penalty {
+                        penalty_type: PenaltyType::TimePenalty,
+                        infringement_type: InfringementType::SmallCollision,
+                        vehicle_id: 1,
+                        other_vehicle_id,
+                        time: 5,
+                        lap_number: 1,
+                        places_gained: 0,
+                    },
+                }),
+            )
+        };
+
+        // 255 means there's no other car involved.
+        race.feed_frame(event(1.0, 255));
+        assert!(race.incidents().is_empty());
+
+        race.feed_frame(event(2.0, 0));
+        assert_eq!(race.incidents().len(), 1);
+        assert_eq!(race.incidents()[0].car.index, 1);
+        assert_eq!(race.incidents()[0].other_car.index, 0);
+
+        let car = race.car(1).unwrap().id();
+        assert_eq!(race.penalty_sheet(car).unwrap().time_penalties, 10);
+    }
+
+    #[test]
+    fn test_rewind() {
+        let mut sheet = PenaltySheet::default();
+        sheet.add(
+            10.0,
+            penalty(PenaltyType::DriveThrough, InfringementType::PitLaneSpeeding),
+        );
+        for (timestamp, pit_status) in [
+            (18.0, PitStatus::None),
+            (19.0, PitStatus::Pitting),
+            (20.0, PitStatus::None),
+        ]
+        .iter()
+        {
+            let mut lap_data = lap_data(1);
+            lap_data.pit_status = *pit_status;
+            sheet.update_lap_data(*timestamp, &lap_data);
+        }
+        assert_eq!(sheet.unserved_drive_throughs(), 0);
+
+        sheet.rewind(15.0);
+        assert_eq!(sheet.drive_throughs, 1);
+        assert_eq!(sheet.unserved_drive_throughs(), 1);
+
+        sheet.rewind(5.0);
+        assert!(sheet.penalties.is_empty());
+        assert_eq!(sheet.drive_throughs, 0);
+    }
+}
@@ -0,0 +1,398 @@
+use std::collections::VecDeque;
+
+use crate::race::{CarId, SpatialLocation, Timestamp};
+
+/// `Retention` determines how many locations `RaceLines` keeps per car.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Retention {
+    /// Keep all locations.
+    Unbounded,
+    /// Keep the most recent locations, up to the given number of locations per car.
+    Locations(usize),
+    /// Keep the locations of the most recent period, in seconds.
+    TimeWindow(f32),
+}
+
+/// `RaceLines` abstracts the racing lines drivers race. In other words: it contains
+/// the path that the drivers drove across the race track.
+///
+/// By default all locations are kept. For long sessions, limit the memory usage by setting a
+/// `Retention` with `RaceLines::with_retention()` and by dropping locations close to each other
+/// with `RaceLines::with_min_distance()`.
+#[derive(Debug, Clone)]
+pub struct RaceLines {
+    lines: Vec<Line>,
+    retention: Retention,
+    min_distance: f32,
+}
+
+// The race line of a single car, ordered by time.
+#[derive(Debug, Clone)]
+struct Line {
+    car: CarId,
+    locations: VecDeque<SpatialLocation>,
+}
+
+impl RaceLines {
+    /// Create `RaceLines` that keep all locations.
+    pub fn new() -> Self {
+        Self::with_retention(Retention::Unbounded)
+    }
+
+    pub fn with_retention(retention: Retention) -> Self {
+        RaceLines {
+            lines: vec![],
+            retention,
+            min_distance: 0.0,
+        }
+    }
+
+    /// Drop locations that are less than `min_distance` metres away from the previous location
+    /// of the same car.
+    pub fn with_min_distance(mut self, min_distance: f32) -> Self {
+        self.min_distance = min_distance;
+        self
+    }
+
+    /// Filter `RaceLines` by car.
+    pub fn by_car(&self, car: CarId) -> RaceLines {
+        RaceLines {
+            lines: self
+                .lines
+                .iter()
+                .filter(|line| line.car == car)
+                .cloned()
+                .collect(),
+            retention: self.retention,
+            min_distance: self.min_distance,
+        }
+    }
+
+    /// Return `RaceLines` as a `Vec`. The locations are ordered by car and then by time.
+    pub fn to_vec(&self) -> Vec<SpatialLocation> {
+        self.iter().cloned().collect()
+    }
+
+    /// Iterate over all locations. The locations are ordered by car and then by time.
+    pub fn iter(&self) -> impl Iterator<Item = &SpatialLocation> + '_ {
+        self.lines.iter().flat_map(|line| line.locations.iter())
+    }
+
+    /// Iterate over the locations of `car`, ordered by time.
+    pub fn iter_car(&self, car: CarId) -> impl Iterator<Item = &SpatialLocation> + '_ {
+        self.lines
+            .iter()
+            .filter(move |line| line.car == car)
+            .flat_map(|line| line.locations.iter())
+    }
+
+    /// Number of locations.
+    pub fn len(&self) -> usize {
+        self.lines.iter().map(|line| line.locations.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the locations with a timestamp between `start` and `end`, inclusive.
+    pub fn between(&self, start: Timestamp, end: Timestamp) -> RaceLines {
+        RaceLines {
+            lines: self
+                .lines
+                .iter()
+                .map(|line| {
+                    let from = line.locations.partition_point(|l| l.timestamp < start);
+                    let to = line.locations.partition_point(|l| l.timestamp <= end);
+                    Line {
+                        car: line.car,
+                        locations: line.locations.range(from..to.max(from)).cloned().collect(),
+                    }
+                })
+                .filter(|line| !line.locations.is_empty())
+                .collect(),
+            retention: self.retention,
+            min_distance: self.min_distance,
+        }
+    }
+
+    /// Return the location of `car` at `timestamp`. The location is linearly interpolated
+    /// between the recorded locations around `timestamp`. Returns `None` if `timestamp` is
+    /// outside the period for which locations of `car` are recorded.
+    pub fn at(&self, car: CarId, timestamp: Timestamp) -> Option<SpatialLocation> {
+        let locations = &self.lines.iter().find(|line| line.car == car)?.locations;
+        let i = locations.partition_point(|l| l.timestamp < timestamp);
+        let after = locations.get(i)?;
+        if after.timestamp == timestamp {
+            return Some(*after);
+        }
+
+        let before = locations.get(i.checked_sub(1)?)?;
+        let t = (timestamp - before.timestamp) / (after.timestamp - before.timestamp);
+        let position = before.position() + (after.position() - before.position()) * t;
+
+        Some(SpatialLocation {
+            car,
+            timestamp,
+            coords: position.to_map_with_elevation(),
+        })
+    }
+
+    /// Return the locations of all cars at `timestamp`. See `RaceLines::at()`.
+    pub fn snapshot(&self, timestamp: Timestamp) -> Vec<SpatialLocation> {
+        self.lines
+            .iter()
+            .filter_map(|line| self.at(line.car, timestamp))
+            .collect()
+    }
+
+    /// Resample the race line of `car` at a fixed `rate` in Hz, starting at the first
+    /// recorded location.
+    pub fn resample(&self, car: CarId, rate: f32) -> Vec<SpatialLocation> {
+        let mut locations = self.iter_car(car);
+        let (first, last) = match (locations.next(), self.iter_car(car).last()) {
+            (Some(first), Some(last)) => (first.timestamp, last.timestamp),
+            _ => return vec![],
+        };
+
+        let samples = ((last - first) * rate) as usize + 1;
+        (0..samples)
+            .filter_map(|i| self.at(car, first + i as f32 / rate))
+            .collect()
+    }
+
+    /// Simplify the race lines with the Douglas-Peucker algorithm. The simplified race line of a
+    /// car deviates at most `epsilon` metres from the original race line.
+    pub fn simplify(&self, epsilon: f32) -> RaceLines {
+        RaceLines {
+            lines: self
+                .lines
+                .iter()
+                .map(|line| {
+                    let locations: Vec<SpatialLocation> = line.locations.iter().cloned().collect();
+                    Line {
+                        car: line.car,
+                        locations: douglas_peucker(&locations, epsilon).into(),
+                    }
+                })
+                .collect(),
+            retention: self.retention,
+            min_distance: self.min_distance,
+        }
+    }
+
+    /// Remove all locations recorded at or after `timestamp`. Use this to discard the part of a
+    /// session that was undone by a flashback.
+    pub fn truncate(&mut self, timestamp: Timestamp) {
+        for line in self.lines.iter_mut() {
+            let len = line.locations.partition_point(|l| l.timestamp < timestamp);
+            line.locations.truncate(len);
+        }
+        self.lines.retain(|line| !line.locations.is_empty());
+    }
+
+    /// Remove all locations.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    pub(crate) fn append(&mut self, point: SpatialLocation) {
+        let index = match self.lines.iter().position(|line| line.car == point.car) {
+            Some(index) => index,
+            None => {
+                self.lines.push(Line {
+                    car: point.car,
+                    locations: VecDeque::new(),
+                });
+                self.lines.len() - 1
+            }
+        };
+        let locations = &mut self.lines[index].locations;
+
+        if let Some(last) = locations.back() {
+            if distance(last, &point) < self.min_distance {
+                return;
+            }
+        }
+        locations.push_back(point);
+
+        match self.retention {
+            Retention::Unbounded => {}
+            Retention::Locations(max) => {
+                while locations.len() > max {
+                    locations.pop_front();
+                }
+            }
+            Retention::TimeWindow(window) => {
+                while let Some(first) = locations.front() {
+                    if first.timestamp >= point.timestamp - window {
+                        break;
+                    }
+                    locations.pop_front();
+                }
+            }
+        }
+    }
+}
+
+impl Default for RaceLines {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn distance(a: &SpatialLocation, b: &SpatialLocation) -> f32 {
+    (a.position() - b.position()).length()
+}
+
+// Distance of `point` to the line segment between `start` and `end`.
+fn distance_to_segment(
+    point: &SpatialLocation,
+    start: &SpatialLocation,
+    end: &SpatialLocation,
+) -> f32 {
+    let segment = end.position() - start.position();
+    let length = segment.dot(segment);
+    if length == 0.0 {
+        return distance(point, start);
+    }
+
+    let t = ((point.position() - start.position()).dot(segment) / length).clamp(0.0, 1.0);
+    (start.position() + segment * t - point.position()).length()
+}
+
+fn douglas_peucker(locations: &[SpatialLocation], epsilon: f32) -> Vec<SpatialLocation> {
+    if locations.len() < 3 {
+        return locations.to_vec();
+    }
+
+    let mut keep = vec![false; locations.len()];
+    keep[0] = true;
+    keep[locations.len() - 1] = true;
+
+    // Use an explicit stack, as a recursive implementation overflows the stack on long laps.
+    let mut stack = vec![(0, locations.len() - 1)];
+    while let Some((start, end)) = stack.pop() {
+        let mut farthest = (start, 0.0);
+        for i in start + 1..end {
+            let d = distance_to_segment(&locations[i], &locations[start], &locations[end]);
+            if d > farthest.1 {
+                farthest = (i, d);
+            }
+        }
+
+        if farthest.1 > epsilon {
+            keep[farthest.0] = true;
+            stack.push((start, farthest.0));
+            stack.push((farthest.0, end));
+        }
+    }
+
+    locations
+        .iter()
+        .zip(keep.iter())
+        .filter(|(_, keep)| **keep)
+        .map(|(location, _)| *location)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::race::{CarId, RaceLines, Retention, SpatialLocation};
+
+    fn location(car: u64, timestamp: f32, x: f32, z: f32) -> SpatialLocation {
+        SpatialLocation {
+            car: CarId::from(car),
+            timestamp,
+            coords: (x, z, 0.0),
+        }
+    }
+
+    #[test]
+    fn test_retention() {
+        let mut race_lines = RaceLines::with_retention(Retention::TimeWindow(2.0));
+        for i in 0..10 {
+            race_lines.append(location(1, i as f32, i as f32, 0.0));
+            race_lines.append(location(2, i as f32, 0.0, i as f32));
+        }
+        assert_eq!(race_lines.len(), 6);
+        assert_eq!(
+            race_lines
+                .iter_car(CarId::from(1))
+                .next()
+                .unwrap()
+                .timestamp,
+            7.0
+        );
+
+        let mut race_lines =
+            RaceLines::with_retention(Retention::Locations(4)).with_min_distance(1.5);
+        for i in 0..10 {
+            race_lines.append(location(1, i as f32, i as f32, 0.0));
+        }
+        let x: Vec<f32> = race_lines.iter().map(|l| l.coords.0).collect();
+        assert_eq!(x, vec![2.0, 4.0, 6.0, 8.0]);
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut race_lines = RaceLines::new();
+        for i in 0..10 {
+            race_lines.append(location(1, i as f32, i as f32, 0.0));
+            race_lines.append(location(2, i as f32 + 5.0, 0.0, i as f32));
+        }
+
+        race_lines.truncate(4.0);
+        assert_eq!(race_lines.len(), 4);
+        assert!(race_lines.iter_car(CarId::from(2)).next().is_none());
+
+        race_lines.append(location(1, 4.0, 40.0, 0.0));
+        assert_eq!(race_lines.at(CarId::from(1), 4.0).unwrap().coords.0, 40.0);
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let mut race_lines = RaceLines::new();
+        for i in 0..10 {
+            race_lines.append(location(1, i as f32, 2.0 * i as f32, 0.0));
+            race_lines.append(location(2, i as f32 + 0.5, 0.0, i as f32));
+        }
+
+        assert_eq!(race_lines.between(2.0, 4.0).len(), 5);
+        assert_eq!(
+            race_lines.at(CarId::from(1), 2.25).unwrap().coords,
+            (4.5, 0.0, 0.0)
+        );
+        assert!(race_lines.at(CarId::from(2), 0.25).is_none());
+
+        let snapshot = race_lines.snapshot(3.0);
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[1].coords, (0.0, 2.5, 0.0));
+
+        assert_eq!(race_lines.resample(CarId::from(1), 4.0).len(), 37);
+    }
+
+    #[test]
+    fn test_simplify() {
+        let mut race_lines = RaceLines::new();
+        for i in 0..=10 {
+            let z = 0.1 * (i % 2) as f32;
+            race_lines.append(location(1, i as f32, i as f32, z));
+        }
+
+        let x: Vec<f32> = race_lines
+            .simplify(0.5)
+            .iter()
+            .map(|l| l.coords.0)
+            .collect();
+        assert_eq!(x, vec![0.0, 10.0]);
+
+        let x: Vec<f32> = race_lines
+            .simplify(0.05)
+            .iter()
+            .map(|l| l.coords.0)
+            .collect();
+        assert_eq!(x.len(), 11);
+    }
+}
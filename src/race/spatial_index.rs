@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use crate::race::{CarId, RaceLines, SpatialLocation};
+use crate::track::Point;
+
+/// `SpatialIndex` divides the ground plane of the track in square cells to quickly find
+/// locations near a point, without scanning all locations.
+///
+/// Build an index over all recorded locations with `RaceLines::spatial_index()` to compare race
+/// lines. Build an index over `RaceLines::snapshot()` to find the cars near a car at a certain
+/// point in time.
+#[derive(Debug, Clone)]
+pub struct SpatialIndex {
+    cell_size: f32,
+    locations: Vec<SpatialLocation>,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    // The lower and upper cell bounds of all locations.
+    bounds: Option<((i32, i32), (i32, i32))>,
+}
+
+impl SpatialIndex {
+    /// Build an index over `locations` with cells of `cell_size` metres. A cell size in the
+    /// order of the search radius performs best.
+    pub fn new<I>(locations: I, cell_size: f32) -> Self
+    where
+        I: IntoIterator<Item = SpatialLocation>,
+    {
+        let mut index = SpatialIndex {
+            cell_size: cell_size.max(f32::EPSILON),
+            locations: vec![],
+            cells: HashMap::new(),
+            bounds: None,
+        };
+
+        for location in locations {
+            let cell = index.cell(location.map_point());
+            index.bounds = Some(match index.bounds {
+                None => (cell, cell),
+                Some((min, max)) => (
+                    (min.0.min(cell.0), min.1.min(cell.1)),
+                    (max.0.max(cell.0), max.1.max(cell.1)),
+                ),
+            });
+            index
+                .cells
+                .entry(cell)
+                .or_default()
+                .push(index.locations.len());
+            index.locations.push(location);
+        }
+
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+
+    /// Return the location of `car` closest to `point`.
+    pub fn nearest(&self, car: CarId, point: Point) -> Option<&SpatialLocation> {
+        let (min, max) = self.bounds?;
+        let center = self.cell(point);
+        let mut nearest: Option<(f32, &SpatialLocation)> = None;
+
+        // Search rings of cells around the cell of `point`. A location in ring `r` is at least
+        // `(r - 1) * cell_size` metres away, so the search stops once that exceeds the distance
+        // to the nearest location found so far, or when the ring falls outside the bounds.
+        for ring in 0.. {
+            if let Some((distance, _)) = nearest {
+                if (ring - 1) as f32 * self.cell_size > distance {
+                    break;
+                }
+            }
+            if center.0 - ring < min.0
+                && center.1 - ring < min.1
+                && center.0 + ring > max.0
+                && center.1 + ring > max.1
+            {
+                break;
+            }
+
+            for cell in ring_cells(center, ring) {
+                for location in self.cell_locations(cell) {
+                    if location.car != car {
+                        continue;
+                    }
+
+                    let distance = distance(location.map_point(), point);
+                    match nearest {
+                        Some((nearest, _)) if nearest <= distance => {}
+                        _ => nearest = Some((distance, location)),
+                    }
+                }
+            }
+        }
+
+        nearest.map(|(_, location)| location)
+    }
+
+    /// Return all locations within `radius` metres of `point`.
+    pub fn within(&self, point: Point, radius: f32) -> Vec<&SpatialLocation> {
+        let (x0, z0) = self.cell((point.0 - radius, point.1 - radius));
+        let (x1, z1) = self.cell((point.0 + radius, point.1 + radius));
+
+        let mut locations = vec![];
+        for x in x0..=x1 {
+            for z in z0..=z1 {
+                locations.extend(
+                    self.cell_locations((x, z))
+                        .filter(|location| distance(location.map_point(), point) <= radius),
+                );
+            }
+        }
+
+        locations
+    }
+
+    /// Return the cars with a location within `radius` metres of `point`. Every car is returned
+    /// once.
+    pub fn cars_within(&self, point: Point, radius: f32) -> Vec<CarId> {
+        let mut cars: Vec<CarId> = vec![];
+        for location in self.within(point, radius) {
+            if !cars.contains(&location.car) {
+                cars.push(location.car);
+            }
+        }
+
+        cars
+    }
+
+    fn cell(&self, point: Point) -> (i32, i32) {
+        (
+            (point.0 / self.cell_size).floor() as i32,
+            (point.1 / self.cell_size).floor() as i32,
+        )
+    }
+
+    fn cell_locations(&self, cell: (i32, i32)) -> impl Iterator<Item = &SpatialLocation> + '_ {
+        self.cells
+            .get(&cell)
+            .into_iter()
+            .flatten()
+            .map(move |i| &self.locations[*i])
+    }
+}
+
+impl RaceLines {
+    /// Build a `SpatialIndex` over all locations. See `SpatialIndex::new()`.
+    pub fn spatial_index(&self, cell_size: f32) -> SpatialIndex {
+        SpatialIndex::new(self.iter().cloned(), cell_size)
+    }
+}
+
+// The cells on the border of the square of cells `ring` cells around `center`.
+fn ring_cells(center: (i32, i32), ring: i32) -> Vec<(i32, i32)> {
+    if ring == 0 {
+        return vec![center];
+    }
+
+    let mut cells = vec![];
+    for i in -ring..=ring {
+        cells.push((center.0 + i, center.1 - ring));
+        cells.push((center.0 + i, center.1 + ring));
+    }
+    for i in -ring + 1..ring {
+        cells.push((center.0 - ring, center.1 + i));
+        cells.push((center.0 + ring, center.1 + i));
+    }
+
+    cells
+}
+
+fn distance(a: Point, b: Point) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::race::spatial_index::SpatialIndex;
+    use crate::race::{CarId, SpatialLocation};
+
+    fn location(car: u64, timestamp: f32, x: f32, z: f32) -> SpatialLocation {
+        SpatialLocation {
+            car: CarId::from(car),
+            timestamp,
+            coords: (x, z, 0.0),
+        }
+    }
+
+    #[test]
+    fn test_spatial_index() {
+        let locations = (0..100).flat_map(|i| {
+            vec![
+                location(1, i as f32, i as f32 * 10.0, 0.0),
+                location(2, i as f32, i as f32 * 10.0, 5.0),
+            ]
+        });
+        let index = SpatialIndex::new(locations, 20.0);
+        assert_eq!(index.len(), 200);
+
+        let nearest = index.nearest(CarId::from(1), (503.0, 40.0)).unwrap();
+        assert_eq!(nearest.timestamp, 50.0);
+        assert!(index.nearest(CarId::from(3), (0.0, 0.0)).is_none());
+
+        assert_eq!(index.within((500.0, 2.5), 4.0).len(), 2);
+        assert_eq!(index.within((505.0, 0.0), 6.0).len(), 2);
+        assert_eq!(index.cars_within((-10.0, 10.0), 12.0), vec![CarId::from(2)]);
+    }
+}
@@ -0,0 +1,297 @@
+use crate::frame::{
+    car_status::{CarStatus, TyreCompound, VisualTyreCompound},
+    lap_data::{LapData, PitStatus},
+};
+use crate::race::Timestamp;
+
+/// `Stint` is a period in which a car drove on the same set of tyres.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stint {
+    pub compound: TyreCompound,
+    pub visual_compound: VisualTyreCompound,
+    /// Lap the stint started.
+    pub start_lap: u8,
+    /// Last lap driven on this set of tyres.
+    pub end_lap: u8,
+    /// Age in laps of the tyres at the start of the stint.
+    pub tyre_age_at_start: u8,
+}
+
+impl Stint {
+    /// Number of laps driven in this stint.
+    pub fn length(&self) -> u8 {
+        self.end_lap.saturating_sub(self.start_lap) + 1
+    }
+}
+
+/// `PitStop` is a visit of a car to the pit lane.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PitStop {
+    /// Lap the car entered the pit lane.
+    pub lap: u8,
+    pub entry_time: Timestamp,
+    /// `None` as long as the car is in the pit lane.
+    pub exit_time: Option<Timestamp>,
+    /// Time in seconds the car was stationary in its pit box.
+    pub stationary_time: f32,
+}
+
+impl PitStop {
+    /// Time in seconds the car spent in the pit lane. `None` as long as the car is in the
+    /// pit lane.
+    pub fn pit_lane_time(&self) -> Option<f32> {
+        self.exit_time.map(|exit_time| exit_time - self.entry_time)
+    }
+}
+
+/// `StintHistory` keeps track of the stints and pit stops of a single car.
+#[derive(Debug, Clone, Default)]
+pub struct StintHistory {
+    pub stints: Vec<Stint>,
+    pub pit_stops: Vec<PitStop>,
+    // Session time at which each stint started.
+    stint_start_times: Vec<Timestamp>,
+    current_lap: u8,
+    last_tyre_age: u8,
+    pit_status: Option<PitStatus>,
+    // Start of the period the car is stationary in its pit box.
+    stationary_since: Option<Timestamp>,
+    // Start and end of every period the car was stationary in its pit box.
+    stationary_periods: Vec<(Timestamp, Timestamp)>,
+}
+
+impl StintHistory {
+    pub(crate) fn update_lap_data(&mut self, timestamp: Timestamp, lap_data: &LapData) {
+        self.current_lap = lap_data.current_lap_num;
+        let in_pit_lane = self.in_pit_lane();
+        if let Some(stint) = self.stints.last_mut() {
+            if !in_pit_lane {
+                stint.end_lap = self.current_lap;
+            }
+        }
+
+        let previous = self.pit_status.replace(lap_data.pit_status);
+        match (previous, lap_data.pit_status) {
+            (Some(PitStatus::None), PitStatus::Pitting)
+            | (Some(PitStatus::None), PitStatus::InPitArea) => {
+                self.pit_stops.push(PitStop {
+                    lap: self.current_lap,
+                    entry_time: timestamp,
+                    exit_time: None,
+                    stationary_time: 0.0,
+                });
+            }
+            (Some(PitStatus::Pitting), PitStatus::None)
+            | (Some(PitStatus::InPitArea), PitStatus::None) => {
+                if let Some(pit_stop) = self.pit_stops.last_mut() {
+                    pit_stop.exit_time = Some(timestamp);
+                }
+            }
+            _ => {}
+        }
+
+        match (self.stationary_since, lap_data.pit_status) {
+            (None, PitStatus::InPitArea) => self.stationary_since = Some(timestamp),
+            (Some(since), PitStatus::Pitting) | (Some(since), PitStatus::None) => {
+                if let Some(pit_stop) = self.pit_stops.last_mut() {
+                    pit_stop.stationary_time += timestamp - since;
+                }
+                self.stationary_periods.push((since, timestamp));
+                self.stationary_since = None;
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn update_car_status(&mut self, timestamp: Timestamp, car_status: &CarStatus) {
+        if car_status.actual_tyre_compound == TyreCompound::Unknown {
+            return;
+        }
+
+        let new_set = match self.stints.last() {
+            Some(stint) => {
+                stint.compound != car_status.actual_tyre_compound
+                    || car_status.tyres_age_laps < self.last_tyre_age
+            }
+            None => true,
+        };
+        self.last_tyre_age = car_status.tyres_age_laps;
+
+        if new_set {
+            self.stints.push(Stint {
+                compound: car_status.actual_tyre_compound,
+                visual_compound: car_status.visual_tyre_compound,
+                start_lap: self.current_lap.max(1),
+                end_lap: self.current_lap.max(1),
+                tyre_age_at_start: car_status.tyres_age_laps,
+            });
+            self.stint_start_times.push(timestamp);
+        }
+    }
+
+    // Undo the stints and pit stops that started at or after `timestamp`. The state of the pit
+    // lane is forgotten, the next Lap Data frame tells where the car is after the flashback.
+    pub(crate) fn rewind(&mut self, timestamp: Timestamp) {
+        let kept = self
+            .stint_start_times
+            .iter()
+            .take_while(|start| **start < timestamp)
+            .count();
+        self.stints.truncate(kept);
+        self.stint_start_times.truncate(kept);
+        self.last_tyre_age = self
+            .stints
+            .last()
+            .map(|stint| stint.tyre_age_at_start)
+            .unwrap_or_default();
+
+        self.pit_stops
+            .retain(|pit_stop| pit_stop.entry_time < timestamp);
+        if let Some(since) = self.stationary_since.take() {
+            self.stationary_periods.push((since, timestamp));
+        }
+        self.stationary_periods
+            .retain(|(start, _)| *start < timestamp);
+        for (_, end) in self.stationary_periods.iter_mut() {
+            *end = end.min(timestamp);
+        }
+        if let Some(pit_stop) = self.pit_stops.last_mut() {
+            if matches!(pit_stop.exit_time, Some(exit_time) if exit_time >= timestamp) {
+                pit_stop.exit_time = None;
+            }
+            pit_stop.stationary_time = self
+                .stationary_periods
+                .iter()
+                .filter(|(start, _)| *start >= pit_stop.entry_time)
+                .map(|(start, end)| end - start)
+                .sum();
+        }
+
+        self.pit_status = None;
+    }
+
+    fn in_pit_lane(&self) -> bool {
+        !matches!(self.pit_status, None | Some(PitStatus::None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::{car_status, lap_data};
+    use crate::frame::{
+        car_status::{CarStatus, TyreCompound, VisualTyreCompound},
+        lap_data::{LapData, PitStatus},
+    };
+    use crate::race::stint::{Stint, StintHistory};
+
+    fn lap(current_lap_num: u8, pit_status: PitStatus) -> LapData {
+        let mut lap_data = lap_data(1);
+        lap_data.current_lap_num = current_lap_num;
+        lap_data.pit_status = pit_status;
+        lap_data
+    }
+
+    fn tyres(compound: TyreCompound, tyres_age_laps: u8) -> CarStatus {
+        let mut car_status = car_status();
+        car_status.actual_tyre_compound = compound;
+        car_status.tyres_age_laps = tyres_age_laps;
+        car_status
+    }
+
+    #[test]
+    fn test_stints() {
+        let mut history = StintHistory::default();
+        let updates = [
+            (1, PitStatus::None, tyres(TyreCompound::C3, 0)),
+            (2, PitStatus::None, tyres(TyreCompound::C3, 1)),
+            // Switched to another compound.
+            (2, PitStatus::Pitting, tyres(TyreCompound::C2, 0)),
+            (3, PitStatus::None, tyres(TyreCompound::C2, 0)),
+            (4, PitStatus::None, tyres(TyreCompound::C2, 1)),
+            // A new set of the same compound.
+            (4, PitStatus::InPitArea, tyres(TyreCompound::C2, 0)),
+            (5, PitStatus::None, tyres(TyreCompound::C2, 0)),
+            (6, PitStatus::None, tyres(TyreCompound::C2, 1)),
+        ];
+        for (i, (lap_num, pit_status, car_status)) in updates.iter().enumerate() {
+            history.update_lap_data(i as f32 * 90.0, &lap(*lap_num, *pit_status));
+            history.update_car_status(i as f32 * 90.0, car_status);
+        }
+
+        let stint = |compound, start_lap, end_lap| Stint {
+            compound,
+            visual_compound: VisualTyreCompound::Medium,
+            start_lap,
+            end_lap,
+            tyre_age_at_start: 0,
+        };
+        assert_eq!(
+            history.stints,
+            vec![
+                stint(TyreCompound::C3, 1, 2),
+                stint(TyreCompound::C2, 2, 4),
+                stint(TyreCompound::C2, 4, 6),
+            ]
+        );
+        assert_eq!(history.stints[1].length(), 3);
+        assert_eq!(history.pit_stops.len(), 2);
+    }
+
+    #[test]
+    fn test_pit_stop() {
+        let mut history = StintHistory::default();
+        history.update_lap_data(10.0, &lap(5, PitStatus::None));
+        history.update_lap_data(11.0, &lap(5, PitStatus::Pitting));
+        assert_eq!(history.pit_stops.len(), 1);
+        assert_eq!(history.pit_stops[0].entry_time, 11.0);
+        assert_eq!(history.pit_stops[0].pit_lane_time(), None);
+
+        history.update_lap_data(20.0, &lap(5, PitStatus::InPitArea));
+        history.update_lap_data(22.5, &lap(5, PitStatus::InPitArea));
+        history.update_lap_data(23.0, &lap(5, PitStatus::Pitting));
+        history.update_lap_data(30.0, &lap(6, PitStatus::None));
+
+        let pit_stop = &history.pit_stops[0];
+        assert_eq!(pit_stop.lap, 5);
+        assert_eq!(pit_stop.exit_time, Some(30.0));
+        assert_eq!(pit_stop.pit_lane_time(), Some(19.0));
+        assert_eq!(pit_stop.stationary_time, 3.0);
+        assert_eq!(history.pit_stops.len(), 1);
+    }
+
+    #[test]
+    fn test_rewind() {
+        let mut history = StintHistory::default();
+        history.update_lap_data(10.0, &lap(5, PitStatus::None));
+        history.update_car_status(10.0, &tyres(TyreCompound::C3, 4));
+        history.update_lap_data(11.0, &lap(5, PitStatus::Pitting));
+        history.update_lap_data(20.0, &lap(5, PitStatus::InPitArea));
+        history.update_car_status(20.0, &tyres(TyreCompound::C2, 0));
+        history.update_lap_data(23.0, &lap(5, PitStatus::Pitting));
+        history.update_lap_data(30.0, &lap(6, PitStatus::None));
+
+        // A flashback to the moment the car was in its pit box, before the tyres were changed.
+        history.rewind(15.0);
+        assert_eq!(history.stints.len(), 1);
+        assert_eq!(history.pit_stops.len(), 1);
+        assert_eq!(history.pit_stops[0].exit_time, None);
+        assert_eq!(history.pit_stops[0].stationary_time, 0.0);
+
+        history.update_lap_data(15.0, &lap(5, PitStatus::InPitArea));
+        history.update_car_status(15.0, &tyres(TyreCompound::C3, 4));
+        history.update_lap_data(18.0, &lap(5, PitStatus::Pitting));
+        history.update_lap_data(25.0, &lap(6, PitStatus::None));
+        history.update_lap_data(26.0, &lap(6, PitStatus::None));
+        assert_eq!(history.stints.len(), 1);
+        assert_eq!(history.stints[0].end_lap, 6);
+        assert_eq!(history.pit_stops[0].pit_lane_time(), Some(14.0));
+        assert_eq!(history.pit_stops[0].stationary_time, 3.0);
+
+        // A flashback to before the pit stop.
+        history.rewind(5.0);
+        assert!(history.stints.is_empty());
+        assert!(history.pit_stops.is_empty());
+    }
+}
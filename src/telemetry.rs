@@ -17,12 +17,22 @@ use crate::error::AppError;
 /// app.start().unwrap();
 /// ```
 use crate::frame::Frame;
-use crate::parser::frame;
+use crate::parser::{frame, header, is_supported};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
 pub struct App {
     socket: std::net::UdpSocket,
-    sender: crossbeam_channel::Sender<Frame>,
-    receiver: crossbeam_channel::Receiver<Frame>,
+    sender: crossbeam_channel::Sender<ReceivedFrame>,
+    receiver: crossbeam_channel::Receiver<ReceivedFrame>,
+    health: Health,
+}
+
+/// `ReceivedFrame` is a `Frame` together with the wall-clock time it was received.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedFrame {
+    pub received_at: SystemTime,
+    pub frame: Frame,
 }
 
 impl App {
@@ -32,24 +42,48 @@ impl App {
             socket,
             sender: s,
             receiver: r,
+            health: Health::default(),
         }
     }
 
+    /// Returns a `Health` handle to monitor the stream of frames. The handle stays valid after
+    /// `App` has been moved to another thread.
+    pub fn health(&self) -> Health {
+        self.health.clone()
+    }
+
     // Read bytes from the socket and parse them as a `Frame`. This method loops till it parsed a
     // frame successfully.
-    fn read_frame(&mut self) -> Result<Frame, AppError> {
+    fn read_frame(&mut self) -> Result<ReceivedFrame, AppError> {
         loop {
             // The biggest frame possible has 1464 bytes.
             let mut buf = [0; 1464];
             let (_, _) = self.socket.recv_from(&mut buf)?;
+            let received_at = SystemTime::now();
+
+            if let Some(frame) = self.parse(&buf) {
+                return Ok(ReceivedFrame { received_at, frame });
+            }
+        }
+    }
+
+    // Parse `packet` as a `Frame` and record the result in the `Health`.
+    fn parse(&self, packet: &[u8]) -> Option<Frame> {
+        // Any error while parsing the frame is silently ignored.
+        // That's ugly. But for now I don't know how to handle the
+        // `Err(VerboseError<u8>)` decently.
+        if let Ok((_, frame)) = frame(packet) {
+            self.health.record_frame();
+            return Some(frame);
+        }
 
-            // Any error while parsing the frame is silently ignored.
-            // That's ugly. But for now I don't know how to handle the
-            // `Err(VerboseError<u8>)` decently.
-            if let Ok((_, frame)) = frame(&buf) {
-                return Ok(frame);
+        match header(packet) {
+            Ok((_, header)) if !is_supported(&header.packet_id) => {
+                self.health.record_unsupported_packet()
             }
+            _ => self.health.record_parse_error(),
         }
+        None
     }
 
     /// Returns an iterator over the `Frame`s. The iterator only yields frames if
@@ -60,6 +94,14 @@ impl App {
         }
     }
 
+    /// Returns an iterator over the `Frame`s together with the wall-clock time they were
+    /// received. See `frames()`.
+    pub fn received_frames(&self) -> ReceivedFrames {
+        ReceivedFrames {
+            inbound: self.receiver.clone(),
+        }
+    }
+
     /// Start reading from the socket.
     pub fn start(&mut self) -> Result<(), crate::error::AppError> {
         loop {
@@ -75,16 +117,201 @@ impl App {
 }
 
 pub struct Frames {
-    inbound: crossbeam_channel::Receiver<Frame>,
+    inbound: crossbeam_channel::Receiver<ReceivedFrame>,
+}
+
+impl Frames {
+    /// Wait at most `timeout` for the next `Frame`. Returns `None` if no frame arrived in time.
+    /// Use this instead of iterating to detect that the game stopped sending frames, for
+    /// example because the player quit to the menu.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Frame> {
+        self.inbound
+            .recv_timeout(timeout)
+            .ok()
+            .map(|received| received.frame)
+    }
 }
 
 impl Iterator for Frames {
     type Item = Frame;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Ok(header) = self.inbound.recv() {
-            return Some(header);
+        if let Ok(received) = self.inbound.recv() {
+            return Some(received.frame);
         }
         None
     }
 }
+
+pub struct ReceivedFrames {
+    inbound: crossbeam_channel::Receiver<ReceivedFrame>,
+}
+
+impl ReceivedFrames {
+    /// Wait at most `timeout` for the next `ReceivedFrame`. See `Frames::recv_timeout()`.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<ReceivedFrame> {
+        self.inbound.recv_timeout(timeout).ok()
+    }
+}
+
+impl Iterator for ReceivedFrames {
+    type Item = ReceivedFrame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inbound.recv().ok()
+    }
+}
+
+/// `Health` tells whether the game is still sending frames.
+#[derive(Clone, Default)]
+pub struct Health {
+    stats: Arc<Mutex<Stats>>,
+}
+
+#[derive(Default)]
+struct Stats {
+    last_frame: Option<Instant>,
+    frames: u64,
+    parse_errors: u64,
+    unsupported_packets: u64,
+}
+
+impl Health {
+    /// The moment the most recent frame was received.
+    pub fn last_frame(&self) -> Option<Instant> {
+        self.stats().last_frame
+    }
+
+    /// Number of frames received.
+    pub fn frames(&self) -> u64 {
+        self.stats().frames
+    }
+
+    /// Number of packets of a supported type that couldn't be parsed as a frame.
+    pub fn parse_errors(&self) -> u64 {
+        self.stats().parse_errors
+    }
+
+    /// Number of packets of a type that isn't parsed, like Car Telemetry or Car Setups.
+    pub fn unsupported_packets(&self) -> u64 {
+        self.stats().unsupported_packets
+    }
+
+    /// Whether no frame has been received for at least `timeout`. A stream that hasn't
+    /// received any frame yet isn't stale.
+    pub fn is_stale(&self, timeout: Duration) -> bool {
+        match self.last_frame() {
+            Some(last_frame) => last_frame.elapsed() >= timeout,
+            None => false,
+        }
+    }
+
+    fn record_frame(&self) {
+        let mut stats = self.stats();
+        stats.last_frame = Some(Instant::now());
+        stats.frames += 1;
+    }
+
+    fn record_parse_error(&self) {
+        self.stats().parse_errors += 1;
+    }
+
+    fn record_unsupported_packet(&self) {
+        self.stats().unsupported_packets += 1;
+    }
+
+    fn stats(&self) -> std::sync::MutexGuard<'_, Stats> {
+        // A panic while holding the lock can't leave the counters in an invalid state.
+        self.stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::lap_data_frame;
+    use crate::telemetry::{App, Frames, Health, ReceivedFrame, ReceivedFrames};
+    use std::net::UdpSocket;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    // A Penalty event.
+    const EVENT: &[u8] = &[
+        228, 7, 1, 19, 1, 3, 207, 82, 48, 29, 211, 221, 97, 126, 56, 88, 29, 66, 196, 1, 0, 0, 19,
+        255, 80, 69, 78, 65, 16, 41, 19, 255, 255, 1, 255,
+    ];
+
+    #[test]
+    fn test_health() {
+        let health = Health::default();
+        assert_eq!(health.last_frame(), None);
+        assert!(!health.is_stale(Duration::from_secs(0)));
+
+        health.record_frame();
+        assert_eq!(health.frames(), 1);
+        assert!(health.last_frame().is_some());
+        assert!(!health.is_stale(Duration::from_secs(60)));
+        assert!(health.is_stale(Duration::from_secs(0)));
+    }
+
+    #[test]
+    fn test_parse() {
+        let app = App::new(UdpSocket::bind("127.0.0.1:0").unwrap());
+        let health = app.health();
+
+        assert!(app.parse(EVENT).is_some());
+
+        // The same header, but for a Car Telemetry packet.
+        let mut car_telemetry = EVENT.to_vec();
+        car_telemetry[5] = 6;
+        assert!(app.parse(&car_telemetry).is_none());
+
+        // The body is cut off.
+        assert!(app.parse(&EVENT[..26]).is_none());
+
+        assert_eq!(health.frames(), 1);
+        assert_eq!(health.unsupported_packets(), 1);
+        assert_eq!(health.parse_errors(), 1);
+    }
+
+    #[test]
+    fn test_recv_timeout() {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let frames = Frames {
+            inbound: receiver.clone(),
+        };
+        let received_frames = ReceivedFrames { inbound: receiver };
+        let received_frame = ReceivedFrame {
+            received_at: UNIX_EPOCH + Duration::from_secs(1_600_000_000),
+            frame: lap_data_frame(1.0, vec![]),
+        };
+
+        sender.send(received_frame.clone()).unwrap();
+        let received = frames.recv_timeout(Duration::from_millis(10));
+        assert_eq!(received, Some(lap_data_frame(1.0, vec![])));
+        assert_eq!(frames.recv_timeout(Duration::from_millis(10)), None);
+
+        sender.send(received_frame.clone()).unwrap();
+        let received = received_frames.recv_timeout(Duration::from_millis(10));
+        assert_eq!(received, Some(received_frame));
+        assert_eq!(
+            received_frames.recv_timeout(Duration::from_millis(10)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_read_frame() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = socket.local_addr().unwrap();
+        let mut app = App::new(socket);
+
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        sender.send_to(EVENT, address).unwrap();
+
+        let before = SystemTime::now();
+        let received = app.read_frame().unwrap();
+        assert!(received.received_at >= before);
+        assert!(received.received_at <= SystemTime::now());
+    }
+}
@@ -0,0 +1,242 @@
+use crate::race::{SpatialLocation, Timestamp};
+use crate::track::Track;
+
+/// Time difference to a `ReferenceLap` at a certain distance along the lap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeltaSample {
+    /// Distance along the lap in metres.
+    pub distance: f32,
+    /// Time difference in seconds. A positive delta means the lap is slower than the reference.
+    pub delta: f32,
+}
+
+/// `ReferenceLap` is a lap other laps are compared against, like a personal best, the best lap
+/// of a teammate or a stored ghost lap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferenceLap {
+    // Pairs of distance along the lap and time since the start of the lap, ordered by distance.
+    samples: Vec<(f32, f32)>,
+}
+
+impl ReferenceLap {
+    /// Build a `ReferenceLap` from the race line of a single lap of a single driver. The
+    /// locations must be ordered by time. Returns `None` if the lap doesn't contain at least 2
+    /// locations that progress along the track.
+    pub fn new(track: &Track, lap: &[SpatialLocation]) -> Option<ReferenceLap> {
+        ReferenceLap::from_samples(profile(track, lap))
+    }
+
+    /// Build a `ReferenceLap` from pairs of distance along the lap in metres and time since the
+    /// start of the lap in seconds, as returned by `samples()`. Use this to restore a stored
+    /// reference lap. Returns `None` if there are less than 2 samples or if the distances don't
+    /// strictly increase.
+    pub fn from_samples(samples: Vec<(f32, f32)>) -> Option<ReferenceLap> {
+        // The time between 2 samples at the same distance can't be interpolated.
+        let increasing = samples.windows(2).all(|pair| pair[0].0 < pair[1].0);
+        if samples.len() < 2 || !increasing {
+            return None;
+        }
+
+        Some(ReferenceLap { samples })
+    }
+
+    /// Pairs of distance along the lap in metres and time since the start of the lap in seconds.
+    pub fn samples(&self) -> &[(f32, f32)] {
+        &self.samples
+    }
+
+    /// Time in seconds the reference lap took to reach `distance` metres along the lap.
+    pub fn time_at(&self, distance: f32) -> f32 {
+        interpolate(&self.samples, distance)
+    }
+
+    /// Compare a lap with the reference lap. Returns the delta at every location of the lap.
+    pub fn delta(&self, track: &Track, lap: &[SpatialLocation]) -> Vec<DeltaSample> {
+        profile(track, lap)
+            .into_iter()
+            .map(|(distance, time)| DeltaSample {
+                distance,
+                delta: time - self.time_at(distance),
+            })
+            .collect()
+    }
+}
+
+/// `LiveDelta` computes the delta to a `ReferenceLap` while a lap is in progress. A lap starts
+/// when a car crosses the start line.
+pub struct LiveDelta {
+    reference: ReferenceLap,
+    track: Track,
+    lap_start: Option<Timestamp>,
+    previous: Option<(f32, Timestamp)>,
+}
+
+impl LiveDelta {
+    pub fn new(track: Track, reference: ReferenceLap) -> Self {
+        LiveDelta {
+            reference,
+            track,
+            lap_start: None,
+            previous: None,
+        }
+    }
+
+    /// Update the delta with the next location of the car. Returns the delta in seconds, or
+    /// `None` if the car hasn't crossed the start line yet.
+    pub fn push(&mut self, location: &SpatialLocation) -> Option<f32> {
+        let distance = match self.previous {
+            Some((previous_distance, _)) => self.track.project_near(location, previous_distance),
+            None => self.track.project(location),
+        }
+        .distance;
+        let half = self.track.length() / 2.0;
+
+        if let Some((previous_distance, previous_time)) = self.previous {
+            // Crossing the start line makes the distance jump from the end of the lap to the
+            // beginning.
+            if previous_distance > half && distance < half {
+                let before = self.track.length() - previous_distance;
+                let fraction = before / (before + distance);
+                self.lap_start =
+                    Some(previous_time + fraction * (location.timestamp - previous_time));
+            }
+        }
+        self.previous = Some((distance, location.timestamp));
+
+        let lap_start = self.lap_start?;
+        Some(location.timestamp - lap_start - self.reference.time_at(distance))
+    }
+}
+
+// Turn the race line of a lap into pairs of distance along the lap and time since crossing the
+// start line. Locations just before the start line get a negative distance. Locations that don't
+// progress along the track are skipped.
+fn profile(track: &Track, lap: &[SpatialLocation]) -> Vec<(f32, f32)> {
+    let half = track.length() / 2.0;
+    let mut samples: Vec<(f32, f32)> = vec![];
+    for location in lap {
+        let mut distance = match samples.last() {
+            Some((last, _)) => track.project_near(location, *last),
+            None => track.project(location),
+        }
+        .distance;
+        if samples.is_empty() && distance > half {
+            distance -= track.length();
+        }
+        if let Some((last, _)) = samples.last() {
+            if distance - last > half {
+                distance -= track.length();
+            } else if last - distance > half {
+                distance += track.length();
+            }
+            if distance <= *last {
+                continue;
+            }
+        }
+
+        samples.push((distance, location.timestamp));
+    }
+
+    if samples.is_empty() {
+        return samples;
+    }
+
+    let start = interpolate(&samples, 0.0);
+    samples
+        .into_iter()
+        .map(|(distance, time)| (distance, time - start))
+        .collect()
+}
+
+// Linear interpolation of the time at `distance` in samples ordered by distance. The first and
+// last 2 samples are used for extrapolation.
+fn interpolate(samples: &[(f32, f32)], distance: f32) -> f32 {
+    if samples.len() == 1 {
+        return samples[0].1;
+    }
+
+    let i = samples
+        .iter()
+        .position(|(d, _)| *d > distance)
+        .unwrap_or(samples.len() - 1)
+        .max(1);
+    let (d0, t0) = samples[i - 1];
+    let (d1, t1) = samples[i];
+
+    t0 + (distance - d0) / (d1 - d0) * (t1 - t0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::race::{CarId, SpatialLocation};
+    use crate::timing::delta::{LiveDelta, ReferenceLap};
+    use crate::track::Track;
+
+    fn location(timestamp: f32, x: f32, z: f32) -> SpatialLocation {
+        SpatialLocation {
+            car: CarId::from(1),
+            timestamp,
+            coords: (x, z, 0.0),
+        }
+    }
+
+    // Drive around a square of 100 by 100 metres, starting at the start line. The first
+    // `slow` metres are driven at 5 m/s, the remainder at 10 m/s.
+    fn lap(start: f32, slow: f32) -> Vec<SpatialLocation> {
+        let mut time = start;
+        (0..40)
+            .map(|i| {
+                let distance = i as f32 * 10.0;
+                let (x, z) = match i / 10 {
+                    0 => (distance, 0.0),
+                    1 => (100.0, distance - 100.0),
+                    2 => (300.0 - distance, 100.0),
+                    _ => (0.0, 400.0 - distance),
+                };
+                let location = location(time, x, z);
+                time += if distance < slow { 2.0 } else { 1.0 };
+                location
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_delta() {
+        let track = Track::from_race_line(&lap(0.0, 0.0)).unwrap();
+        let reference = ReferenceLap::new(&track, &lap(0.0, 0.0)).unwrap();
+        assert_eq!(reference.time_at(150.0), 15.0);
+
+        let delta = reference.delta(&track, &lap(100.0, 50.0));
+        assert_eq!(delta[0].delta, 0.0);
+        assert_eq!(delta[5].delta, 5.0);
+        assert_eq!(delta.last().unwrap().delta, 5.0);
+
+        let mut live = LiveDelta::new(track, reference);
+        let mut laps = lap(0.0, 0.0);
+        laps.extend(lap(40.0, 20.0));
+        let deltas: Vec<Option<f32>> = laps.iter().map(|l| live.push(l)).collect();
+        assert_eq!(deltas[39], None);
+        assert_eq!(deltas[40], Some(0.0));
+        assert_eq!(deltas[79], Some(2.0));
+    }
+
+    #[test]
+    fn test_from_samples() {
+        let reference = ReferenceLap::from_samples(vec![(0.0, 0.0), (10.0, 1.0), (30.0, 2.0)]);
+        assert_eq!(reference.unwrap().time_at(20.0), 1.5);
+
+        assert_eq!(ReferenceLap::from_samples(vec![(0.0, 0.0)]), None);
+        assert_eq!(
+            ReferenceLap::from_samples(vec![(0.0, 0.0), (10.0, 1.0), (10.0, 1.5)]),
+            None
+        );
+        assert_eq!(
+            ReferenceLap::from_samples(vec![(0.0, 0.0), (30.0, 2.0), (10.0, 1.0)]),
+            None
+        );
+        assert_eq!(
+            ReferenceLap::from_samples(vec![(0.0, 0.0), (f32::NAN, 1.0)]),
+            None
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+
+use crate::race::{CarId, SpatialLocation, Timestamp};
+use crate::track::Track;
+
+/// Time a car needed for a mini-sector.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MiniSectorTime {
+    pub car: CarId,
+    /// Index of the mini-sector, starting at 0.
+    pub sector: usize,
+    /// Time in seconds.
+    pub time: f32,
+    /// Whether the time is the best time of the car in this mini-sector.
+    pub personal_best: bool,
+    /// Whether the time is the best time of all cars in this mini-sector.
+    pub overall_best: bool,
+}
+
+/// Mini-sector times of a single car.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DriverMiniSectors {
+    /// Times in seconds of every lap, indexed by lap and mini-sector. A mini-sector is `None`
+    /// if the driver didn't drive through it completely, for example because the timing
+    /// started halfway the lap.
+    pub laps: Vec<Vec<Option<f32>>>,
+    /// Best time in seconds of every mini-sector.
+    pub personal_best: Vec<Option<f32>>,
+    // The mini-sector the driver is in and the time the driver entered it.
+    current: Option<(usize, Timestamp)>,
+    previous: Option<(f32, Timestamp)>,
+}
+
+impl DriverMiniSectors {
+    /// The lap time if all personal best mini-sectors were combined in a single lap. `None` as
+    /// long as not every mini-sector has a time.
+    pub fn theoretical_best(&self) -> Option<f32> {
+        sum(&self.personal_best)
+    }
+}
+
+/// `MiniSectorTiming` divides a lap in mini-sectors of equal length and records the time every
+/// car needs to drive through every mini-sector.
+pub struct MiniSectorTiming {
+    track: Track,
+    sectors: usize,
+    cars: HashMap<CarId, DriverMiniSectors>,
+    overall_best: Vec<Option<f32>>,
+}
+
+impl MiniSectorTiming {
+    /// Divide the lap of `track` in `sectors` mini-sectors.
+    pub fn new(track: Track, sectors: usize) -> Self {
+        let sectors = sectors.max(1);
+        MiniSectorTiming {
+            track,
+            sectors,
+            cars: HashMap::new(),
+            overall_best: vec![None; sectors],
+        }
+    }
+
+    /// Length of a mini-sector in metres.
+    pub fn sector_length(&self) -> f32 {
+        self.track.length() / self.sectors as f32
+    }
+
+    /// Update the timing with the next location of a car. Returns the time of the
+    /// mini-sector the driver just completed, if any.
+    pub fn feed(&mut self, location: &SpatialLocation) -> Option<MiniSectorTime> {
+        let sectors = self.sectors;
+        let length = self.track.length();
+        let sector_length = self.sector_length();
+        let driver = self
+            .cars
+            .entry(location.car)
+            .or_insert_with(|| DriverMiniSectors {
+                laps: vec![],
+                personal_best: vec![None; sectors],
+                current: None,
+                previous: None,
+            });
+
+        let distance = match driver.previous {
+            Some((previous_distance, _)) => self.track.project_near(location, previous_distance),
+            None => self.track.project(location),
+        }
+        .distance;
+        let sector = ((distance / sector_length) as usize).min(sectors - 1);
+
+        let previous = driver.previous.replace((distance, location.timestamp));
+        let (previous_distance, previous_time) = previous?;
+        let previous_sector = ((previous_distance / sector_length) as usize).min(sectors - 1);
+        if sector == previous_sector {
+            return None;
+        }
+
+        // Only a move to the next mini-sector is timed. Any other move, like a car that
+        // is reset to the track, invalidates the mini-sector.
+        if sector != (previous_sector + 1) % sectors {
+            driver.current = None;
+            return None;
+        }
+
+        let mut distance = distance;
+        if distance < previous_distance {
+            distance += length;
+        }
+        let boundary = (previous_sector + 1) as f32 * sector_length;
+        let fraction = (boundary - previous_distance) / (distance - previous_distance);
+        let crossed_at = previous_time + fraction * (location.timestamp - previous_time);
+
+        let entered = driver.current.replace((sector, crossed_at));
+        if sector == 0 || driver.laps.is_empty() {
+            driver.laps.push(vec![None; sectors]);
+        }
+
+        let (_, entered_at) = entered?;
+        let time = crossed_at - entered_at;
+        let lap = if sector == 0 {
+            driver.laps.len() - 2
+        } else {
+            driver.laps.len() - 1
+        };
+        driver.laps[lap][previous_sector] = Some(time);
+
+        let personal_best = is_best(&mut driver.personal_best[previous_sector], time);
+        let overall_best = is_best(&mut self.overall_best[previous_sector], time);
+
+        Some(MiniSectorTime {
+            car: location.car,
+            sector: previous_sector,
+            time,
+            personal_best,
+            overall_best,
+        })
+    }
+
+    /// Return the mini-sector times of `car`.
+    pub fn car(&self, car: CarId) -> Option<&DriverMiniSectors> {
+        self.cars.get(&car)
+    }
+
+    /// Best time in seconds of every mini-sector over all cars.
+    pub fn overall_best(&self) -> &[Option<f32>] {
+        &self.overall_best
+    }
+
+    /// The lap time if all overall best mini-sectors were combined in a single lap.
+    pub fn theoretical_best(&self) -> Option<f32> {
+        sum(&self.overall_best)
+    }
+}
+
+// Replace `best` by `time` if `time` is better. Returns whether `time` is the new best.
+fn is_best(best: &mut Option<f32>, time: f32) -> bool {
+    match best {
+        Some(best) if *best <= time => false,
+        _ => {
+            *best = Some(time);
+            true
+        }
+    }
+}
+
+fn sum(times: &[Option<f32>]) -> Option<f32> {
+    times
+        .iter()
+        .try_fold(0.0, |total, time| time.map(|t| total + t))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::race::{CarId, SpatialLocation};
+    use crate::timing::mini_sector::MiniSectorTiming;
+    use crate::track::Track;
+
+    // A location on a square track of 100 by 100 metres, given the distance along the lap.
+    fn location(car: CarId, timestamp: f32, distance: f32) -> SpatialLocation {
+        let distance = distance % 400.0;
+        let coords = match (distance / 100.0) as u32 {
+            0 => (distance, 0.0, 0.0),
+            1 => (100.0, distance - 100.0, 0.0),
+            2 => (300.0 - distance, 100.0, 0.0),
+            _ => (0.0, 400.0 - distance, 0.0),
+        };
+        SpatialLocation {
+            car,
+            timestamp,
+            coords,
+        }
+    }
+
+    #[test]
+    fn test_mini_sectors() {
+        let lap: Vec<SpatialLocation> = (0..4)
+            .map(|i| location(CarId::from(1), 0.0, i as f32 * 100.0))
+            .collect();
+        let mut timing = MiniSectorTiming::new(Track::from_race_line(&lap).unwrap(), 8);
+        assert_eq!(timing.sector_length(), 50.0);
+
+        // Start at 10 metres, driving 10 m/s for one lap and 20 m/s the next lap.
+        for i in 1..=80 {
+            let distance = i as f32 * 10.0;
+            let timestamp = if distance <= 410.0 {
+                i as f32
+            } else {
+                41.0 + (i - 41) as f32 / 2.0
+            };
+            timing.feed(&location(CarId::from(1), timestamp, distance));
+        }
+
+        let driver = timing.car(CarId::from(1)).unwrap();
+        assert_eq!(driver.laps.len(), 3);
+        assert_eq!(driver.laps[0][0], None);
+        assert_eq!(driver.laps[0][1], Some(5.0));
+        assert_eq!(driver.laps[1][0], Some(3.0));
+        assert_eq!(driver.laps[1][1], Some(2.5));
+        assert_eq!(driver.personal_best[1], Some(2.5));
+        assert_eq!(driver.theoretical_best(), Some(20.5));
+        assert_eq!(timing.theoretical_best(), Some(20.5));
+    }
+}
@@ -0,0 +1,353 @@
+pub mod segment;
+
+use crate::race::SpatialLocation;
+
+/// A point on the ground plane of the track. The first coordinate is the x-axis, the second
+/// coordinate the z-axis of the game world; just like the first 2 coordinates of
+/// `SpatialLocation::coords`.
+pub type Point = (f32, f32);
+
+/// Distance in metres before and after the previous distance along the lap that
+/// `Track::project_point_near()` searches.
+pub const SEARCH_WINDOW: f32 = 200.0;
+/// Distance in metres to the centreline beyond which `Track::project_point_near()` searches the
+/// whole lap.
+pub const MAX_OFFSET: f32 = 50.0;
+
+/// `Track` models a race track by the centreline of a single lap. It maps locations on the
+/// track to the distance along the lap and the offset to the centreline.
+///
+/// The centreline is a closed polyline: the last point connects to the first one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    centreline: Vec<Point>,
+    // Distance along the lap of every point of the centreline.
+    distances: Vec<f32>,
+    length: f32,
+}
+
+/// Location on the track relative to the centreline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Projection {
+    /// Distance along the lap in metres.
+    pub distance: f32,
+    /// Distance to the centreline in metres. The offset is positive for locations to the left
+    /// of the centreline and negative for locations to the right, when looking in the direction
+    /// of travel and plotting the x-axis to the right and the z-axis upwards.
+    pub lateral_offset: f32,
+}
+
+impl Track {
+    /// Build a `Track` from the race line of a single lap of a single driver. The locations must
+    /// be ordered by time. Returns `None` if the lap contains less than 2 distinct locations.
+    pub fn from_race_line(lap: &[SpatialLocation]) -> Option<Track> {
+        let mut centreline: Vec<Point> = Vec::with_capacity(lap.len());
+        for location in lap {
+            let point = location.map_point();
+            if centreline.last() != Some(&point) {
+                centreline.push(point);
+            }
+        }
+
+        let mut distances = Vec::with_capacity(centreline.len());
+        let mut distance = 0.0;
+        for (i, point) in centreline.iter().enumerate() {
+            if i > 0 {
+                distance += length(centreline[i - 1], *point);
+            }
+            distances.push(distance);
+        }
+
+        Track::new(centreline, distances)
+    }
+
+    /// Build a `Track` from locations of which the distance along the lap is known, like
+    /// `LapData::lap_distance`. The samples don't have to be ordered. Samples with a negative
+    /// distance are ignored. Returns `None` if less than 2 samples remain.
+    pub fn from_lap_distances(samples: &[(f32, SpatialLocation)]) -> Option<Track> {
+        let mut samples: Vec<(f32, Point)> = samples
+            .iter()
+            .filter(|(distance, _)| *distance >= 0.0)
+            .map(|(distance, location)| (*distance, location.map_point()))
+            .collect();
+        samples.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        samples.dedup_by(|a, b| a.0 == b.0);
+
+        let (distances, centreline) = samples.into_iter().unzip();
+        Track::new(centreline, distances)
+    }
+
+    fn new(centreline: Vec<Point>, distances: Vec<f32>) -> Option<Track> {
+        if centreline.len() < 2 {
+            return None;
+        }
+
+        // The closing segment runs from the last point to the first point of the next lap. The
+        // first point isn't necessarily at distance 0.
+        let last = centreline.len() - 1;
+        let length = distances[last] - distances[0] + length(centreline[last], centreline[0]);
+
+        Some(Track {
+            centreline,
+            distances,
+            length,
+        })
+    }
+
+    /// Length of the lap in metres.
+    pub fn length(&self) -> f32 {
+        self.length
+    }
+
+    /// The centreline of the track.
+    pub fn centreline(&self) -> &[Point] {
+        &self.centreline
+    }
+
+    /// Project a location onto the centreline.
+    pub fn project(&self, location: &SpatialLocation) -> Projection {
+        self.project_point(location.map_point())
+    }
+
+    /// Project a point onto the centreline.
+    pub fn project_point(&self, point: Point) -> Projection {
+        self.project_segments(point, |_, _| true)
+            .unwrap_or(Projection {
+                distance: 0.0,
+                lateral_offset: f32::INFINITY,
+            })
+    }
+
+    /// Project a location onto the centreline near `previous_distance`. See
+    /// `project_point_near()`.
+    pub fn project_near(&self, location: &SpatialLocation, previous_distance: f32) -> Projection {
+        self.project_point_near(location.map_point(), previous_distance)
+    }
+
+    /// Project a point onto the part of the centreline within `SEARCH_WINDOW` metres of
+    /// `previous_distance`, the distance along the lap of the previous location of the same car.
+    /// Where parts of the track run side by side or cross, this keeps the projection on the part
+    /// the car is driving on. Falls back to `project_point()` if the point is more than
+    /// `MAX_OFFSET` metres away from that part, for example because the car was reset to the
+    /// track.
+    pub fn project_point_near(&self, point: Point, previous_distance: f32) -> Projection {
+        let hint = previous_distance.rem_euclid(self.length);
+        let near = |start: f32, end: f32| {
+            let on_segment = (hint - start).rem_euclid(self.length) <= end - start;
+            let ahead = (start - hint).rem_euclid(self.length);
+            let behind = (hint - end).rem_euclid(self.length);
+            on_segment || ahead <= SEARCH_WINDOW || behind <= SEARCH_WINDOW
+        };
+
+        match self.project_segments(point, near) {
+            Some(projection) if projection.lateral_offset.abs() <= MAX_OFFSET => projection,
+            _ => self.project_point(point),
+        }
+    }
+
+    // Project a point onto the closest of the segments for which `include` returns true, given
+    // the start and end distance of the segment. Returns `None` if no segment is included.
+    fn project_segments<F>(&self, point: Point, include: F) -> Option<Projection>
+    where
+        F: Fn(f32, f32) -> bool,
+    {
+        let mut best: Option<Projection> = None;
+
+        for i in 0..self.centreline.len() {
+            let (a, b, start, end) = self.segment(i);
+            let (dx, dz) = (b.0 - a.0, b.1 - a.1);
+            let segment_length = (dx * dx + dz * dz).sqrt();
+            if segment_length == 0.0 || !include(start, end) {
+                continue;
+            }
+
+            let (px, pz) = (point.0 - a.0, point.1 - a.1);
+            let t = ((px * dx + pz * dz) / (segment_length * segment_length)).clamp(0.0, 1.0);
+            let closest = (a.0 + t * dx, a.1 + t * dz);
+            let offset = length(closest, point);
+
+            let closer = match best {
+                Some(best) => offset < best.lateral_offset.abs(),
+                None => true,
+            };
+            if closer {
+                let cross = dx * pz - dz * px;
+                best = Some(Projection {
+                    distance: (start + t * (end - start)) % self.length,
+                    lateral_offset: if cross < 0.0 { -offset } else { offset },
+                });
+            }
+        }
+
+        best
+    }
+
+    /// Return the point on the centreline at `distance` metres along the lap.
+    pub fn point_at(&self, distance: f32) -> Point {
+        let mut distance = distance.rem_euclid(self.length);
+        let i = match self
+            .distances
+            .binary_search_by(|d| d.partial_cmp(&distance).unwrap_or(std::cmp::Ordering::Less))
+        {
+            Ok(i) => i,
+            // Before the first point, on the closing segment of the previous lap.
+            Err(0) => {
+                distance += self.length;
+                self.centreline.len() - 1
+            }
+            Err(i) => i - 1,
+        };
+
+        let (a, b, start, end) = self.segment(i);
+        if end <= start {
+            return a;
+        }
+
+        let t = (distance - start) / (end - start);
+        (a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1))
+    }
+
+    // Return the start point, end point, start distance and end distance of the i-th segment
+    // of the centreline. The last segment closes the lap: it ends at the first point of the
+    // next lap.
+    fn segment(&self, i: usize) -> (Point, Point, f32, f32) {
+        let next = (i + 1) % self.centreline.len();
+        let end = if next == 0 {
+            self.distances[0] + self.length
+        } else {
+            self.distances[next]
+        };
+        (
+            self.centreline[i],
+            self.centreline[next],
+            self.distances[i],
+            end,
+        )
+    }
+}
+
+fn length(a: Point, b: Point) -> f32 {
+    ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::race::{CarId, SpatialLocation};
+    use crate::track::{Projection, Track};
+
+    fn location(x: f32, z: f32) -> SpatialLocation {
+        SpatialLocation {
+            car: CarId::from(1),
+            timestamp: 0.0,
+            coords: (x, z, 0.0),
+        }
+    }
+
+    #[test]
+    fn test_project() {
+        // A square of 100 by 100 metres, driven counter clockwise.
+        let lap = [
+            location(0.0, 0.0),
+            location(100.0, 0.0),
+            location(100.0, 100.0),
+            location(0.0, 100.0),
+        ];
+        let track = Track::from_race_line(&lap).unwrap();
+        assert_eq!(track.length(), 400.0);
+
+        assert_eq!(
+            track.project(&location(50.0, 5.0)),
+            Projection {
+                distance: 50.0,
+                lateral_offset: 5.0
+            }
+        );
+        assert_eq!(
+            track.project(&location(105.0, 50.0)),
+            Projection {
+                distance: 150.0,
+                lateral_offset: -5.0
+            }
+        );
+        assert_eq!(
+            track.project(&location(-2.0, 50.0)),
+            Projection {
+                distance: 350.0,
+                lateral_offset: -2.0
+            }
+        );
+        assert_eq!(track.point_at(250.0), (50.0, 100.0));
+        assert_eq!(track.point_at(-50.0), (0.0, 50.0));
+    }
+
+    #[test]
+    fn test_project_near() {
+        // A hairpin: a straight of 1000 metres and a straight back, 20 metres apart. The lap
+        // starts halfway the first straight. There's a point every 50 metres.
+        let corners = [
+            (500.0, 0.0),
+            (1000.0, 0.0),
+            (1000.0, 20.0),
+            (0.0, 20.0),
+            (0.0, 0.0),
+            (500.0, 0.0),
+        ];
+        let mut lap = vec![];
+        for pair in corners.windows(2) {
+            let ((x0, z0), (x1, z1)): ((f32, f32), (f32, f32)) = (pair[0], pair[1]);
+            let steps = ((x1 - x0).abs().max((z1 - z0).abs()) / 50.0).ceil() as usize;
+            for i in 0..steps {
+                let t = i as f32 / steps as f32;
+                lap.push(location(x0 + t * (x1 - x0), z0 + t * (z1 - z0)));
+            }
+        }
+        let track = Track::from_race_line(&lap).unwrap();
+        assert_eq!(track.length(), 2040.0);
+
+        // Slightly closer to the straight back, but the car is on the first straight.
+        let point = (750.0, 11.0);
+        assert_eq!(track.project_point(point).distance, 770.0);
+        assert_eq!(
+            track.project_point_near(point, 245.0),
+            Projection {
+                distance: 250.0,
+                lateral_offset: 11.0
+            }
+        );
+        assert_eq!(track.project_point_near(point, 760.0).distance, 770.0);
+
+        // The search continues across the start line.
+        let point = (550.0, 11.0);
+        assert_eq!(track.project_point(point).distance, 970.0);
+        assert_eq!(track.project_point_near(point, 2030.0).distance, 50.0);
+
+        // A car that is far away from the part of the track it was on is searched for on the
+        // whole lap.
+        assert_eq!(
+            track.project_point_near((750.0, 70.0), 245.0).distance,
+            770.0
+        );
+    }
+
+    #[test]
+    fn test_from_lap_distances() {
+        // The same square, but the first sample is 10 metres into the lap.
+        let samples = [
+            (300.0, location(0.0, 100.0)),
+            (10.0, location(10.0, 0.0)),
+            (100.0, location(100.0, 0.0)),
+            (-5.0, location(-5.0, 0.0)),
+            (400.0, location(0.0, 0.0)),
+            (200.0, location(100.0, 100.0)),
+        ];
+        let track = Track::from_lap_distances(&samples).unwrap();
+        assert_eq!(track.length(), 400.0);
+        assert_eq!(track.centreline().len(), 5);
+
+        assert_eq!(track.point_at(0.0), (0.0, 0.0));
+        assert_eq!(track.point_at(5.0), (5.0, 0.0));
+        assert_eq!(track.point_at(10.0), (10.0, 0.0));
+        assert_eq!(track.point_at(395.0), (0.0, 5.0));
+        assert_eq!(track.project(&location(5.0, 1.0)).distance, 5.0);
+    }
+}
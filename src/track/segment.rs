@@ -0,0 +1,478 @@
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+
+use crate::frame::session::Circuit;
+use crate::race::SpatialLocation;
+use crate::track::{Point, Track};
+
+// Distance in metres between the points at which the curvature and speed are evaluated.
+const STEP: f32 = 5.0;
+// The curvature in a point is derived from the change of heading over this distance, in metres,
+// before and after that point.
+const WINDOW: f32 = 15.0;
+// Parts of the track with a radius of more than 250 metres are considered straight.
+const MIN_CURVATURE: f32 = 1.0 / 250.0;
+// Bends with a radius below 100 metres are always a corner, even without a drop in speed.
+const SHARP_CURVATURE: f32 = 1.0 / 100.0;
+// Other bends are only a corner if the speed drops by at least this fraction.
+const MIN_SPEED_DROP: f32 = 0.05;
+// Bends separated by less than this distance, in metres, are treated as a single corner.
+const MIN_GAP: f32 = 40.0;
+// Distance in metres before a corner in which the entry speed is determined.
+const BRAKING_ZONE: f32 = 150.0;
+
+/// A corner of a track. All distances are in metres along the lap. If `exit` is smaller than
+/// `entry`, the corner crosses the start line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Corner {
+    /// Number of the corner. The corner the start line lies in, or else the first corner after
+    /// the start line, is number 1.
+    pub number: u8,
+    pub entry: f32,
+    /// Point of the corner with the lowest speed.
+    pub apex: f32,
+    pub exit: f32,
+}
+
+/// A straight of a track. All distances are in metres along the lap. If `end` is smaller than
+/// `start`, the straight crosses the start line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Straight {
+    pub start: f32,
+    pub end: f32,
+}
+
+/// `Segmentation` divides the lap of a track in corners and straights.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segmentation {
+    pub circuit: Circuit,
+    /// Length of the lap in metres.
+    pub length: f32,
+    pub corners: Vec<Corner>,
+    pub straights: Vec<Straight>,
+}
+
+impl Segmentation {
+    /// Detect the corners and straights of `circuit` from the race line of a single lap. The
+    /// locations must be ordered by time and the lap must start at the start line. Use
+    /// `detect_from_lap_distances()` for laps that start elsewhere.
+    ///
+    /// A corner is a part of the track with a high curvature in which the car slows down. Very
+    /// sharp bends are a corner even if the car doesn't slow down. Returns `None` if the lap
+    /// contains less than 2 distinct locations.
+    pub fn detect(circuit: Circuit, lap: &[SpatialLocation]) -> Option<Segmentation> {
+        let mut distance = 0.0;
+        let samples: Vec<(f32, SpatialLocation)> = lap
+            .iter()
+            .enumerate()
+            .map(|(i, location)| {
+                if i > 0 {
+                    distance += planar_distance(&lap[i - 1], location);
+                }
+                (distance, *location)
+            })
+            .collect();
+
+        Segmentation::detect_from_lap_distances(circuit, &samples)
+    }
+
+    /// Detect the corners and straights of `circuit` from a single lap of locations of which
+    /// the distance along the lap is known, like `LapData::lap_distance`. The locations must be
+    /// ordered by time, but the lap can start anywhere. See `detect()`.
+    pub fn detect_from_lap_distances(
+        circuit: Circuit,
+        samples: &[(f32, SpatialLocation)],
+    ) -> Option<Segmentation> {
+        let track = Track::from_lap_distances(samples)?;
+        let length = track.length();
+        let speeds = speed_profile(samples, length);
+        let steps = (length / STEP) as usize;
+
+        let curvature: Vec<f32> = (0..steps)
+            .map(|i| curvature(&track, i as f32 * STEP))
+            .collect();
+        let speed: Vec<Option<f32>> = (0..steps)
+            .map(|i| speed_at(&speeds, i as f32 * STEP, length))
+            .collect();
+
+        // Find ranges of high curvature and merge ranges that are close together. A range that
+        // crosses the start line ends at an index beyond `steps`.
+        let mut bends: Vec<(usize, usize)> = vec![];
+        for (i, kappa) in curvature.iter().enumerate() {
+            if kappa.abs() < MIN_CURVATURE {
+                continue;
+            }
+
+            match bends.last_mut() {
+                Some((_, end)) if (i - *end) as f32 * STEP <= MIN_GAP => *end = i,
+                _ => bends.push((i, i)),
+            }
+        }
+        if bends.len() > 1 {
+            let (first_start, first_end) = bends[0];
+            let last = bends.len() - 1;
+            if (first_start + steps - bends[last].1) as f32 * STEP <= MIN_GAP {
+                bends.remove(0);
+                bends[last - 1].1 = first_end + steps;
+            }
+        }
+
+        let mut corners: Vec<Corner> = vec![];
+        for (start, end) in bends {
+            let range = || (start..=end).map(|i| i % steps);
+            let sharpest = range().fold(0.0_f32, |max, i| max.max(curvature[i].abs()));
+
+            let slowest = range()
+                .filter_map(|i| speed[i].map(|speed| (i, speed)))
+                .fold(
+                    None,
+                    |slowest: Option<(usize, f32)>, (i, speed)| match slowest {
+                        Some((_, s)) if s <= speed => slowest,
+                        _ => Some((i, speed)),
+                    },
+                );
+
+            // On laps shorter than the braking zone, the whole lap is used.
+            let braking_zone = ((BRAKING_ZONE / STEP) as usize).min(steps);
+            let entry_speed = (start + steps - braking_zone..=start + steps)
+                .filter_map(|i| speed[i % steps])
+                .fold(0.0_f32, f32::max);
+
+            let (apex, speed_drop) = match slowest {
+                Some((i, speed)) if entry_speed > 0.0 => (i, 1.0 - speed / entry_speed),
+                _ => {
+                    let i = range()
+                        .max_by(|a, b| {
+                            curvature[*a]
+                                .abs()
+                                .partial_cmp(&curvature[*b].abs())
+                                .unwrap_or(std::cmp::Ordering::Equal)
+                        })
+                        .unwrap_or(start);
+                    (i, 0.0)
+                }
+            };
+
+            if speed_drop < MIN_SPEED_DROP && sharpest < SHARP_CURVATURE {
+                continue;
+            }
+
+            corners.push(Corner {
+                number: 0,
+                entry: start as f32 * STEP,
+                apex: apex as f32 * STEP,
+                exit: (end % steps) as f32 * STEP,
+            });
+        }
+
+        // A corner that crosses the start line is found last, but the start line lies in it.
+        if let Some(last) = corners.last() {
+            if last.exit < last.entry {
+                corners.rotate_right(1);
+            }
+        }
+        for (i, corner) in corners.iter_mut().enumerate() {
+            corner.number = i as u8 + 1;
+        }
+
+        let mut straights: Vec<Straight> = corners
+            .windows(2)
+            .map(|pair| Straight {
+                start: pair[0].exit,
+                end: pair[1].entry,
+            })
+            .collect();
+        match (corners.first(), corners.last()) {
+            (Some(first), Some(last)) => straights.push(Straight {
+                start: last.exit,
+                end: first.entry,
+            }),
+            _ => straights.push(Straight {
+                start: 0.0,
+                end: length,
+            }),
+        }
+
+        Some(Segmentation {
+            circuit,
+            length,
+            corners,
+            straights,
+        })
+    }
+
+    /// Return the corner at `distance` metres along the lap, if any.
+    pub fn corner_at(&self, distance: f32) -> Option<&Corner> {
+        self.corners.iter().find(|corner| {
+            if corner.entry <= corner.exit {
+                corner.entry <= distance && distance <= corner.exit
+            } else {
+                corner.entry <= distance || distance <= corner.exit
+            }
+        })
+    }
+}
+
+/// `SegmentationStore` persists `Segmentation`s in a directory, one file per circuit. This allows
+/// reuse of a `Segmentation` across sessions.
+pub struct SegmentationStore {
+    directory: PathBuf,
+}
+
+impl SegmentationStore {
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        SegmentationStore {
+            directory: directory.into(),
+        }
+    }
+
+    /// Load the `Segmentation` of `circuit`. Returns `Ok(None)` if no `Segmentation` has been
+    /// stored for the circuit.
+    pub fn load(&self, circuit: Circuit) -> std::io::Result<Option<Segmentation>> {
+        let path = self.path(circuit);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let mut segmentation = Segmentation {
+            circuit,
+            length: 0.0,
+            corners: vec![],
+            straights: vec![],
+        };
+
+        for line in fs::read_to_string(path)?.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["length", length] => segmentation.length = number(length)?,
+                ["corner", n, entry, apex, exit] => segmentation.corners.push(Corner {
+                    number: n
+                        .parse()
+                        .map_err(|_| invalid_data(format!("{:?} is not a valid number", n)))?,
+                    entry: number(entry)?,
+                    apex: number(apex)?,
+                    exit: number(exit)?,
+                }),
+                ["straight", start, end] => segmentation.straights.push(Straight {
+                    start: number(start)?,
+                    end: number(end)?,
+                }),
+                [] => {}
+                _ => return Err(invalid_data(format!("{:?} is not a valid line", line))),
+            }
+        }
+
+        Ok(Some(segmentation))
+    }
+
+    /// Store `segmentation`, replacing the `Segmentation` stored for the same circuit.
+    pub fn save(&self, segmentation: &Segmentation) -> std::io::Result<()> {
+        let mut content = format!("length {}\n", segmentation.length);
+        for corner in segmentation.corners.iter() {
+            content.push_str(&format!(
+                "corner {} {} {} {}\n",
+                corner.number, corner.entry, corner.apex, corner.exit
+            ));
+        }
+        for straight in segmentation.straights.iter() {
+            content.push_str(&format!("straight {} {}\n", straight.start, straight.end));
+        }
+
+        fs::create_dir_all(&self.directory)?;
+        fs::write(self.path(segmentation.circuit), content)
+    }
+
+    fn path(&self, circuit: Circuit) -> PathBuf {
+        self.directory.join(format!("{:?}.segments", circuit))
+    }
+}
+
+fn number(value: &str) -> std::io::Result<f32> {
+    value
+        .parse()
+        .map_err(|_| invalid_data(format!("{:?} is not a valid number", value)))
+}
+
+fn invalid_data(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+// Calculate the curvature, in 1/metres, of the track at `distance` metres along the lap.
+fn curvature(track: &Track, distance: f32) -> f32 {
+    let before = track.point_at(distance - WINDOW);
+    let point = track.point_at(distance);
+    let after = track.point_at(distance + WINDOW);
+
+    let a = (point.0 - before.0, point.1 - before.1);
+    let b = (after.0 - point.0, after.1 - point.1);
+    let angle = (a.0 * b.1 - a.1 * b.0).atan2(a.0 * b.0 + a.1 * b.1);
+
+    angle / WINDOW
+}
+
+// Distance in metres between 2 locations on the ground plane.
+fn planar_distance(a: &SpatialLocation, b: &SpatialLocation) -> f32 {
+    let (a, b): (Point, Point) = (a.map_point(), b.map_point());
+    ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt()
+}
+
+// Derive the speed, in metres per second, along the lap from the time between locations.
+// Returns pairs of distance along the lap and speed.
+fn speed_profile(samples: &[(f32, SpatialLocation)], length: f32) -> Vec<(f32, f32)> {
+    let mut profile = vec![];
+    for pair in samples.windows(2) {
+        let (distance, a) = pair[0];
+        let (_, b) = pair[1];
+        let step = planar_distance(&a, &b);
+        let time = b.timestamp - a.timestamp;
+
+        if time > 0.0 {
+            profile.push(((distance + step / 2.0).rem_euclid(length), step / time));
+        }
+    }
+
+    profile
+}
+
+// Average speed in the window around `distance`. The window can cross the start line of a lap
+// of `length` metres.
+fn speed_at(profile: &[(f32, f32)], distance: f32, length: f32) -> Option<f32> {
+    let speeds: Vec<f32> = profile
+        .iter()
+        .filter(|(d, _)| {
+            let gap = (d - distance).rem_euclid(length);
+            gap.min(length - gap) <= WINDOW
+        })
+        .map(|(_, speed)| *speed)
+        .collect();
+
+    if speeds.is_empty() {
+        return None;
+    }
+
+    Some(speeds.iter().sum::<f32>() / speeds.len() as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frame::session::Circuit;
+    use crate::race::{CarId, SpatialLocation};
+    use crate::track::segment::Segmentation;
+
+    // A single lap of a stadium shaped track with 2 straights of 500 metres and 2 hairpins with
+    // a radius of 50 metres, starting at the start line. The car drives 80 m/s on the straights
+    // and 25 m/s in the hairpins. Returns pairs of distance along the lap and location.
+    fn stadium() -> Vec<(f32, SpatialLocation)> {
+        let mut lap = vec![];
+        let mut timestamp = 0.0;
+        let mut distance = 0.0;
+        let mut add = |x: f32, z: f32, step: f32, speed: f32| {
+            timestamp += step / speed;
+            lap.push((
+                distance,
+                SpatialLocation {
+                    car: CarId::from(1),
+                    timestamp,
+                    coords: (x, z, 0.0),
+                },
+            ));
+            distance += step;
+        };
+
+        let radius = 50.0;
+        let arc = std::f32::consts::PI * radius / 50.0;
+        for i in 0..100 {
+            add(i as f32 * 5.0, 0.0, 5.0, 80.0);
+        }
+        for i in 0..50 {
+            let angle = i as f32 * std::f32::consts::PI / 50.0 - std::f32::consts::FRAC_PI_2;
+            add(
+                500.0 + radius * angle.cos(),
+                radius + radius * angle.sin(),
+                arc,
+                25.0,
+            );
+        }
+        for i in 0..100 {
+            add(500.0 - i as f32 * 5.0, 2.0 * radius, 5.0, 80.0);
+        }
+        for i in 0..50 {
+            let angle = i as f32 * std::f32::consts::PI / 50.0 + std::f32::consts::FRAC_PI_2;
+            add(
+                radius * angle.cos(),
+                radius + radius * angle.sin(),
+                arc,
+                25.0,
+            );
+        }
+
+        lap
+    }
+
+    #[test]
+    fn test_detect() {
+        let lap: Vec<SpatialLocation> = stadium().into_iter().map(|(_, l)| l).collect();
+        let segmentation = Segmentation::detect(Circuit::Monza, &lap).unwrap();
+        assert_eq!(segmentation.corners.len(), 2);
+        assert_eq!(segmentation.straights.len(), 2);
+
+        // The second hairpin ends at the start line, so the start line lies in corner 1.
+        let first = &segmentation.corners[0];
+        assert_eq!(first.number, 1);
+        assert!(first.exit < first.entry);
+        assert_eq!(segmentation.corner_at(0.0), Some(first));
+
+        let second = &segmentation.corners[1];
+        assert_eq!(second.number, 2);
+        assert!(second.entry > 450.0 && second.entry < 520.0);
+        assert!(second.apex > second.entry && second.apex < second.exit);
+        assert!(segmentation.corner_at(second.apex).is_some());
+        assert!(segmentation.corner_at(250.0).is_none());
+    }
+
+    #[test]
+    fn test_detect_from_lap_distances() {
+        // The same lap, starting halfway the straight back.
+        let lap = stadium();
+        let lap_time = lap.last().unwrap().1.timestamp;
+        let mut samples = lap[200..].to_vec();
+        samples.extend(lap[..200].iter().map(|(distance, location)| {
+            let mut location = *location;
+            location.timestamp += lap_time;
+            (*distance, location)
+        }));
+
+        let locations: Vec<SpatialLocation> = lap.iter().map(|(_, l)| *l).collect();
+        let expected = Segmentation::detect(Circuit::Monza, &locations).unwrap();
+        let segmentation = Segmentation::detect_from_lap_distances(Circuit::Monza, &samples);
+        let segmentation = segmentation.unwrap();
+        assert_eq!(segmentation.corners.len(), expected.corners.len());
+        for (corner, expected) in segmentation.corners.iter().zip(expected.corners.iter()) {
+            assert_eq!(corner.number, expected.number);
+            assert!((corner.entry - expected.entry).abs() <= 10.0);
+            assert!((corner.exit - expected.exit).abs() <= 10.0);
+        }
+        assert_eq!(segmentation.corner_at(0.0).unwrap().number, 1);
+    }
+
+    #[test]
+    fn test_detect_short_lap() {
+        // A circle with a circumference of about 94 metres, shorter than the braking zone.
+        let radius = 15.0;
+        let lap: Vec<SpatialLocation> = (0..40)
+            .map(|i| {
+                let angle = i as f32 * 2.0 * std::f32::consts::PI / 40.0;
+                SpatialLocation {
+                    car: CarId::from(1),
+                    timestamp: i as f32 * 0.2,
+                    coords: (radius * angle.cos(), radius * angle.sin(), 0.0),
+                }
+            })
+            .collect();
+
+        let segmentation = Segmentation::detect(Circuit::Monza, &lap).unwrap();
+        assert!(segmentation.length < 150.0);
+        assert_eq!(segmentation.corners.len(), 1);
+    }
+}